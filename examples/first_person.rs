@@ -9,8 +9,8 @@ use bevy::{
 };
 use bevy_framepace::{FramepacePlugin, FramepaceSettings, Limiter};
 use bevy_mod_wanderlust::{
-    ControllerBundle, ControllerInput, ControllerPhysicsBundle, RapierPhysicsBundle,
-    WanderlustPlugin,
+    camera_relative_movement, ControllerBundle, ControllerInput, ControllerPhysicsBundle,
+    RapierPhysicsBundle, WallCast, WallCaster, WanderlustPlugin,
 };
 use bevy_rapier3d::prelude::*;
 use std::f32::consts::FRAC_2_PI;
@@ -103,6 +103,9 @@ fn setup(
                 ..default()
             },
             ColliderMassProperties::Density(50.0),
+            // Lets the controller stop pushing into the walls/corner below instead of jittering.
+            WallCaster::default(),
+            WallCast::default(),
             Name::from("Player"),
             PlayerBody,
         ))
@@ -241,21 +244,21 @@ fn movement_input(
 
     let mut player_input = body.single_mut();
 
-    let mut dir = Vec3::ZERO;
+    let mut dir = Vec2::ZERO;
     if input.pressed(KeyCode::A) {
-        dir += -tf.right();
+        dir.x -= 1.0;
     }
     if input.pressed(KeyCode::D) {
-        dir += tf.right();
+        dir.x += 1.0;
     }
     if input.pressed(KeyCode::S) {
-        dir += -tf.forward();
+        dir.y -= 1.0;
     }
     if input.pressed(KeyCode::W) {
-        dir += tf.forward();
+        dir.y += 1.0;
     }
-    dir.y = 0.0;
-    player_input.movement = dir.normalize_or_zero();
+    let (_, rotation, _) = tf.to_scale_rotation_translation();
+    player_input.movement = camera_relative_movement(dir.normalize_or_zero(), rotation, Vec3::Y);
 
     player_input.jumping = input.pressed(KeyCode::Space);
 }
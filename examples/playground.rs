@@ -164,6 +164,7 @@ pub fn player(
                     movement: Movement {
                         acceleration: Strength::Scaled(5.0),
                         max_speed: 5.0,
+                        sprint_speed: 15.0,
                         //slip_force_scale: Vec3::splat(0.95),
                         ..default()
                     },
@@ -681,7 +682,7 @@ pub fn oscillating(time: Res<Time>, mut oscillators: Query<(&mut Velocity, &Osci
 }
 
 fn movement_input(
-    mut body: Query<(&mut ControllerInput, &mut Movement), With<PlayerBody>>,
+    mut body: Query<&mut ControllerInput, With<PlayerBody>>,
     camera: Query<&PlayerCam>,
     input: Res<Input<KeyCode>>,
 ) {
@@ -690,13 +691,7 @@ fn movement_input(
     let right = camera_dir * Vec3::X;
     let forward = camera_dir * -Vec3::Z;
 
-    let (mut player_input, mut movement) = body.single_mut();
-
-    if input.pressed(KeyCode::ShiftLeft) {
-        movement.max_speed = 15.0;
-    } else {
-        movement.max_speed = 5.0;
-    }
+    let mut player_input = body.single_mut();
 
     let mut dir = Vec3::ZERO;
     if input.pressed(KeyCode::A) {
@@ -715,6 +710,7 @@ fn movement_input(
     player_input.movement = dir.normalize_or_zero();
 
     player_input.jumping = input.pressed(KeyCode::Space);
+    player_input.sprinting = input.pressed(KeyCode::ShiftLeft);
 }
 
 fn mouse_look(
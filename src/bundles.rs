@@ -1,6 +1,11 @@
-use crate::{Controller, ControllerInput, ControllerPhysicsBundle, RapierPhysicsBundle};
+use crate::{
+    Controller, ControllerInput, ControllerPhysicsBundle, Float, Gravity, GroundCaster, Jump,
+    Movement, RapierPhysicsBundle, Strength, Upright,
+};
 
 use bevy::prelude::*;
+#[cfg(feature = "rapier")]
+use bevy_rapier3d::prelude::LockedAxes;
 
 /// The recommended bundle for creating a character controller. Includes the necessary components for a character controller
 /// as well as many physics-related components that can be used to tweak the behavior of the controller. Try using the
@@ -52,4 +57,151 @@ impl ControllerBundle {
     pub fn starship() -> Self {
         Self { ..default() }
     }
+
+    /// A human-sized character: the default capsule floating just above the ground,
+    /// a single jump, and rotation locked so it always stays upright with gravity.
+    ///
+    /// This is a good starting point for first/third person games.
+    pub fn humanoid() -> Self {
+        Self {
+            #[cfg(feature = "rapier")]
+            rapier_physics: RapierPhysicsBundle {
+                locked_axes: LockedAxes::ROTATION_LOCKED_X | LockedAxes::ROTATION_LOCKED_Z,
+                ..default()
+            },
+            ..default()
+        }
+    }
+
+    /// A snappy platformer character.
+    ///
+    /// Gravity is much stronger than reality so jumps are short and not floaty,
+    /// acceleration/deceleration are high so movement responds immediately, and
+    /// coyote time/jump buffering are kept short but forgiving of slightly mistimed inputs.
+    pub fn platformer() -> Self {
+        let mut bundle = Self::humanoid();
+
+        let controller = &mut bundle.controller;
+        controller.gravity.acceleration = -30.0;
+        controller.movement.acceleration = Strength::Scaled(25.0);
+        controller.movement.deceleration = 40.0;
+        controller.movement.max_speed = 8.0;
+        controller.movement.air_control = 0.8;
+        controller.jump.initial_force = 60.0;
+        controller.jump.coyote_duration = 0.12;
+        controller.jump.buffer_duration = 0.15;
+
+        bundle
+    }
+
+    /// Start building this bundle, see [`ControllerBuilder`].
+    pub fn builder() -> ControllerBuilder {
+        ControllerBuilder::default()
+    }
+}
+
+/// Builder for a [`ControllerBundle`], anything not set is left as the default.
+///
+/// ```ignore
+/// ControllerBundle::builder()
+///     .max_speed(8.0)
+///     .jumps(2)
+///     .float_distance(0.75)
+///     .transform(Transform::from_xyz(0.0, 3.0, 0.0))
+///     .build()
+/// ```
+#[derive(Default)]
+pub struct ControllerBuilder {
+    bundle: ControllerBundle,
+}
+
+impl ControllerBuilder {
+    /// Replace the whole [`Movement`] component.
+    pub fn movement(mut self, movement: Movement) -> Self {
+        self.bundle.controller.movement = movement;
+        self
+    }
+
+    /// Set [`Movement::max_speed`].
+    pub fn max_speed(mut self, max_speed: f32) -> Self {
+        self.bundle.controller.movement.max_speed = max_speed;
+        self
+    }
+
+    /// Set [`Movement::sprint_speed`].
+    pub fn sprint_speed(mut self, sprint_speed: f32) -> Self {
+        self.bundle.controller.movement.sprint_speed = sprint_speed;
+        self
+    }
+
+    /// Set [`Movement::acceleration`].
+    pub fn acceleration(mut self, acceleration: Strength) -> Self {
+        self.bundle.controller.movement.acceleration = acceleration;
+        self
+    }
+
+    /// Replace the whole [`Jump`] component.
+    pub fn jump(mut self, jump: Jump) -> Self {
+        self.bundle.controller.jump = jump;
+        self
+    }
+
+    /// Set [`Jump::initial_force`].
+    pub fn jump_force(mut self, initial_force: f32) -> Self {
+        self.bundle.controller.jump.initial_force = initial_force;
+        self
+    }
+
+    /// Set [`Jump::jumps`].
+    pub fn jumps(mut self, jumps: u32) -> Self {
+        self.bundle.controller.jump.jumps = jumps;
+        self
+    }
+
+    /// Replace the whole [`Float`] component.
+    pub fn float(mut self, float: Float) -> Self {
+        self.bundle.controller.float = float;
+        self
+    }
+
+    /// Set [`Float::distance`].
+    pub fn float_distance(mut self, distance: f32) -> Self {
+        self.bundle.controller.float.distance = distance;
+        self
+    }
+
+    /// Replace the whole [`Gravity`] component.
+    pub fn gravity(mut self, gravity: Gravity) -> Self {
+        self.bundle.controller.gravity = gravity;
+        self
+    }
+
+    /// Set [`Gravity::acceleration`].
+    pub fn gravity_acceleration(mut self, acceleration: f32) -> Self {
+        self.bundle.controller.gravity.acceleration = acceleration;
+        self
+    }
+
+    /// Replace the whole [`Upright`] component.
+    pub fn upright(mut self, upright: Upright) -> Self {
+        self.bundle.controller.upright = upright;
+        self
+    }
+
+    /// Replace the whole [`GroundCaster`] component.
+    pub fn ground_caster(mut self, ground_caster: GroundCaster) -> Self {
+        self.bundle.controller.ground_caster = ground_caster;
+        self
+    }
+
+    /// Set the starting [`Transform`].
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.bundle.transform = transform;
+        self
+    }
+
+    /// Finish building the bundle.
+    pub fn build(self) -> ControllerBundle {
+        self.bundle
+    }
 }
@@ -0,0 +1,81 @@
+use crate::controller::*;
+use bevy::ecs::system::{StaticSystemParam, SystemParam};
+
+/// The physics engine the controller runs on.
+///
+/// Reading the controller's mass and velocity, and casting for the ground, go through this so
+/// the spring/controller logic isn't tied to a specific engine.
+/// [`RapierBackend`](crate::RapierBackend) implements it for rapier. Walls, ceilings, steps,
+/// ledges and water still use rapier directly.
+///
+/// The methods take the [`SystemParam`]'s item, so systems can be generic over the backend with
+/// [`StaticSystemParam`], e.g. [`find_ground`].
+pub trait PhysicsBackend: SystemParam + 'static {
+    /// Length of the next physics step, see [`sync_physics_dt`](crate::sync_physics_dt).
+    fn dt(backend: &Self::Item<'_, '_>) -> f32;
+
+    /// Mass of the rigid body `entity`, `None` if it isn't one.
+    fn mass(backend: &Self::Item<'_, '_>, entity: Entity) -> Option<ControllerMass>;
+
+    /// Velocity of the rigid body `entity`, `None` if it isn't one.
+    fn velocity(backend: &Self::Item<'_, '_>, entity: Entity) -> Option<ControllerVelocity>;
+
+    /// Velocity at `point` of whatever the collider `entity` is attached to, e.g. a moving platform.
+    ///
+    /// Colliders that aren't attached to a rigid body are treated as not moving.
+    fn point_velocity(backend: &Self::Item<'_, '_>, entity: Entity, point: Vec3) -> PointVelocity;
+
+    /// Cast for the ground below the controller `entity`, configured by its `caster`.
+    ///
+    /// `None` if there is nothing to cast with, e.g. the controller doesn't have a collider.
+    fn cast_ground(
+        backend: &Self::Item<'_, '_>,
+        entity: Entity,
+        caster: &GroundCaster,
+        transform: &GlobalTransform,
+        up_vector: Vec3,
+        gizmos: Option<&mut Gizmos>,
+    ) -> Option<GroundHits>;
+
+    /// Is `entity` touching anything?
+    fn in_contact(backend: &Self::Item<'_, '_>, entity: Entity) -> bool;
+}
+
+/// Velocity of a rigid body at a point on it, see [`PhysicsBackend::point_velocity`].
+#[derive(Clone, Copy)]
+pub struct PointVelocity {
+    /// The rigid body, or the collider itself if it isn't attached to one.
+    pub body: Entity,
+    /// Velocity of the rigid body.
+    pub velocity: ControllerVelocity,
+    /// Linear velocity at the point, including the body's rotation (`linear + angular × r`).
+    pub point: Vec3,
+}
+
+/// Sync the physics engine's masses over to our masses.
+///
+/// This is the mass the physics engine computes from the collider, mass-relative strengths like
+/// [`SpringStrength::AngularFrequency`](crate::SpringStrength::AngularFrequency) and
+/// [`Jump::mass_relative`] use it to behave the same at any mass.
+pub fn sync_mass<B: PhysicsBackend>(
+    mut query: Query<(Entity, &mut ControllerMass)>,
+    backend: StaticSystemParam<B>,
+) {
+    for (entity, mut mass) in &mut query {
+        if let Some(body_mass) = B::mass(&backend, entity) {
+            *mass = body_mass;
+        }
+    }
+}
+
+/// Sync the physics engine's velocities over to our velocities.
+pub fn sync_velocity<B: PhysicsBackend>(
+    mut query: Query<(Entity, &mut ControllerVelocity)>,
+    backend: StaticSystemParam<B>,
+) {
+    for (entity, mut velocity) in &mut query {
+        if let Some(body_velocity) = B::velocity(&backend, entity) {
+            *velocity = body_velocity;
+        }
+    }
+}
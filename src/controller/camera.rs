@@ -0,0 +1,100 @@
+use crate::controller::*;
+
+/// Procedural head bob and landing dip for a first person camera, driven by the controller's motion.
+///
+/// This only calculates `translation`/`rotation`, apply them to the camera yourself,
+/// e.g. by adding them to the local [`Transform`] of a camera parented to the controller.
+///
+/// This isn't part of [`Controller`], add it to controllers that should drive a camera.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct CameraMotion {
+    /// Head bob cycles per second while moving at `bob_reference_speed`.
+    pub bob_frequency: f32,
+    /// Vertical head bob distance while moving at `bob_reference_speed` or faster.
+    pub bob_amplitude: f32,
+    /// Side to side roll (in radians) of the head bob while moving at `bob_reference_speed` or faster.
+    pub bob_roll: f32,
+    /// Horizontal speed the head bob is tuned for, the frequency and amplitude scale with speed up to this.
+    pub bob_reference_speed: f32,
+    /// How quickly the head bob fades in and out when starting/stopping or leaving the ground, higher is faster.
+    pub bob_smoothing: f32,
+    /// How far the camera dips per unit of [`LandedEvent::impact_velocity`].
+    pub landing_dip: f32,
+    /// Furthest the camera can dip from landing.
+    pub max_landing_dip: f32,
+    /// How quickly the camera recovers from a landing dip, higher is faster.
+    pub landing_recovery: f32,
+
+    /// Current phase of the head bob, in radians.
+    pub bob_phase: f32,
+    /// Current strength of the head bob, eased towards the speed scale.
+    pub bob_weight: f32,
+    /// Current landing dip distance.
+    pub dip: f32,
+
+    /// Calculated camera offset, in the controller's local space.
+    pub translation: Vec3,
+    /// Calculated camera rotation offset, in the controller's local space.
+    pub rotation: Quat,
+}
+
+impl Default for CameraMotion {
+    fn default() -> Self {
+        Self {
+            bob_frequency: 1.8,
+            bob_amplitude: 0.05,
+            bob_roll: 0.01,
+            bob_reference_speed: 5.0,
+            bob_smoothing: 10.0,
+            landing_dip: 0.02,
+            max_landing_dip: 0.3,
+            landing_recovery: 6.0,
+
+            bob_phase: 0.0,
+            bob_weight: 0.0,
+            dip: 0.0,
+
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+        }
+    }
+}
+
+/// Calculate the [`CameraMotion`] offsets from the controller's velocity, groundedness and landings.
+pub fn camera_motion(
+    mut query: Query<(&mut CameraMotion, &ControllerVelocity, &Gravity, &Grounded)>,
+    mut landed: EventReader<LandedEvent>,
+    ctx: Res<RapierContext>,
+) {
+    let dt = ctx.integration_parameters.dt;
+
+    for event in landed.iter() {
+        let Ok((mut motion, ..)) = query.get_mut(event.entity) else { continue };
+        let dip = motion.dip + event.impact_velocity.max(0.0) * motion.landing_dip;
+        motion.dip = dip.min(motion.max_landing_dip);
+    }
+
+    for (mut motion, velocity, gravity, grounded) in &mut query {
+        let speed = velocity.horizontal_speed(gravity.up_vector);
+        let scale = if **grounded && motion.bob_reference_speed > 0.0 {
+            (speed / motion.bob_reference_speed).min(1.0)
+        } else {
+            0.0
+        };
+
+        let smoothing = 1.0 - (-motion.bob_smoothing * dt).exp();
+        motion.bob_weight += (scale - motion.bob_weight) * smoothing;
+        motion.bob_phase = (motion.bob_phase
+            + std::f32::consts::TAU * motion.bob_frequency * scale * dt)
+            .rem_euclid(std::f32::consts::TAU);
+        motion.dip *= (-motion.landing_recovery * dt).exp();
+
+        // Bob up and down twice per side to side sway, like footsteps.
+        let bob = (motion.bob_phase * 2.0).sin() * motion.bob_amplitude * motion.bob_weight;
+        let roll = motion.bob_phase.sin() * motion.bob_roll * motion.bob_weight;
+
+        motion.translation = Vec3::Y * (bob - motion.dip);
+        motion.rotation = Quat::from_rotation_z(roll);
+    }
+}
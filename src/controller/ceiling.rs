@@ -0,0 +1,112 @@
+use crate::controller::*;
+
+/// Detecting ceilings above the controller, so jumping into one stops the controller's
+/// upward velocity and ends the jump instead of sticking to it.
+///
+/// This uses the [`GroundCaster`]'s exclusions and collision groups for what counts as a ceiling.
+///
+/// This isn't part of [`Controller`], add it to controllers that should bonk off of ceilings.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct CeilingCaster {
+    /// How far above the collider to look for a ceiling.
+    pub cast_length: f32,
+    /// Override the shape used for the cast, the controller's collider is used if this is `None`.
+    #[reflect(ignore)]
+    pub cast_collider: Option<Collider>,
+}
+
+impl Default for CeilingCaster {
+    fn default() -> Self {
+        Self {
+            cast_length: 0.05,
+            cast_collider: None,
+        }
+    }
+}
+
+/// Sent when a controller moving upwards hits a ceiling.
+#[derive(Event, Debug, Clone)]
+pub struct CeilingHitEvent {
+    /// Controller that hit the ceiling.
+    pub entity: Entity,
+    /// Ceiling the controller hit.
+    pub ceiling: Entity,
+    /// Upward speed of the controller that was cancelled.
+    pub impact_velocity: f32,
+}
+
+/// Controllers that can hit their head, and the forces cancelled when they do.
+type CeilingBonkQuery = (
+    Entity,
+    &'static GlobalTransform,
+    &'static CeilingCaster,
+    &'static GroundCaster,
+    &'static Gravity,
+    &'static ControllerVelocity,
+    &'static ControllerMass,
+    &'static mut Jump,
+    &'static mut JumpForce,
+    &'static mut FloatForce,
+);
+
+/// Cancel the upward velocity of controllers that hit a ceiling.
+pub fn ceiling_bonk(
+    mut query: Query<CeilingBonkQuery, Simulated>,
+    colliders: Query<&Collider>,
+    ctx: Res<RapierContext>,
+    mut events: EventWriter<CeilingHitEvent>,
+) {
+    let dt = ctx.integration_parameters.dt;
+    for (
+        entity,
+        tf,
+        caster,
+        ground_caster,
+        gravity,
+        velocity,
+        mass,
+        mut jump,
+        mut jump_force,
+        mut float_force,
+    ) in &mut query
+    {
+        let upward_speed = velocity.vertical_speed(gravity.up_vector);
+        if upward_speed <= 0.0 {
+            continue;
+        }
+
+        let Ok(collider) = colliders.get(entity) else { continue };
+        let shape = caster.cast_collider.as_ref().unwrap_or(collider);
+
+        let predicate =
+            |collider| collider != entity && !ground_caster.exclude_from_ground.contains(&collider);
+        let filter = QueryFilter::new()
+            .exclude_sensors()
+            .groups(ground_caster.ground_groups)
+            .predicate(&predicate);
+
+        let (_, rotation, position) = tf.to_scale_rotation_translation();
+        let Some((ceiling, _)) = ctx.cast_shape(
+            position,
+            rotation,
+            gravity.up_vector,
+            shape,
+            caster.cast_length,
+            filter,
+        ) else {
+            continue;
+        };
+
+        // Stop moving up, and stop the jump from pushing us back into the ceiling.
+        jump.jump_timer = 0.0;
+        jump_force.linear = -gravity.up_vector * upward_speed * mass.mass / dt;
+        float_force.linear = Vec3::ZERO;
+
+        events.send(CeilingHitEvent {
+            entity,
+            ceiling: ctx.collider_parent(ceiling).unwrap_or(ceiling),
+            impact_velocity: upward_speed,
+        });
+    }
+}
@@ -0,0 +1,89 @@
+use crate::controller::*;
+
+/// Crouching, which shrinks the controller's collider and floats it closer to the ground.
+///
+/// The change in float distance is eased by [`Float::distance_smoothing`].
+///
+/// This isn't part of [`Controller`], add it to controllers that should be able to crouch.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct Crouch {
+    /// Collider to use while standing.
+    #[reflect(ignore)]
+    pub standing_collider: Collider,
+    /// Collider to use while crouching.
+    #[reflect(ignore)]
+    pub crouching_collider: Collider,
+    /// [`Float::distance`] to use while standing.
+    pub standing_float_distance: f32,
+    /// [`Float::distance`] to use while crouching.
+    pub crouching_float_distance: f32,
+    /// Is the controller currently crouched.
+    ///
+    /// This can stay true after [`ControllerInput::crouching`] is released if
+    /// there isn't enough room above the controller to stand back up.
+    pub crouched: bool,
+}
+
+impl Default for Crouch {
+    fn default() -> Self {
+        Self {
+            standing_collider: Collider::capsule(Vec3::ZERO, Vec3::new(0.0, 0.5, 0.0), 0.3),
+            crouching_collider: Collider::capsule(Vec3::ZERO, Vec3::new(0.0, 0.1, 0.0), 0.3),
+            standing_float_distance: 0.55,
+            crouching_float_distance: 0.35,
+            crouched: false,
+        }
+    }
+}
+
+/// Swap between the standing and crouching colliders/float distances.
+pub fn crouch(
+    mut query: Query<(
+        Entity,
+        &GlobalTransform,
+        &Gravity,
+        &ControllerInput,
+        &mut Crouch,
+        &mut Float,
+        &mut Collider,
+    )>,
+    ctx: Res<RapierContext>,
+) {
+    for (entity, tf, gravity, input, mut crouch, mut float, mut collider) in &mut query {
+        if input.crouching == crouch.crouched {
+            continue;
+        }
+
+        if input.crouching {
+            crouch.crouched = true;
+            *collider = crouch.crouching_collider.clone();
+            float.distance = crouch.crouching_float_distance;
+            continue;
+        }
+
+        // Make sure we have the headroom to stand back up before we do,
+        // otherwise we'd end up clipping into the ceiling.
+        let (_, rotation, position) = tf.to_scale_rotation_translation();
+        let rise = (crouch.standing_float_distance - crouch.crouching_float_distance).max(0.0);
+        let filter = QueryFilter::new()
+            .exclude_sensors()
+            .exclude_rigid_body(entity);
+        let blocked = ctx
+            .cast_shape(
+                position,
+                rotation,
+                gravity.up_vector,
+                &crouch.standing_collider,
+                rise,
+                filter,
+            )
+            .is_some();
+
+        if !blocked {
+            crouch.crouched = false;
+            *collider = crouch.standing_collider.clone();
+            float.distance = crouch.standing_float_distance;
+        }
+    }
+}
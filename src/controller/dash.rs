@@ -0,0 +1,224 @@
+use crate::controller::*;
+
+/// Quickly dashing in the movement (or facing) direction when [`ControllerInput::dashing`] is pressed.
+///
+/// This isn't part of [`Controller`], add a [`DashBundle`] to controllers that should be able to dash.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct Dash {
+    /// Speed to move at while dashing.
+    pub speed: f32,
+    /// How long a dash lasts.
+    pub duration: f32,
+    /// How long after a dash ends before another can start.
+    pub cooldown_duration: f32,
+    /// Only allow starting a dash while grounded.
+    pub ground_only: bool,
+    /// Ignore gravity while dashing, so the dash moves in a straight line.
+    ///
+    /// If false, only the horizontal velocity is controlled by the dash.
+    pub ignore_gravity: bool,
+    /// Timer for tracking `duration`, this is the remaining time of the current dash.
+    pub dash_timer: f32,
+    /// Timer for tracking `cooldown_duration`, this counts down from the start of a dash.
+    pub cooldown_timer: f32,
+    /// Direction of the current dash, locked in when the dash starts.
+    pub direction: Vec3,
+    /// Was [`ControllerInput::dashing`] true last frame.
+    pub pressed_last_frame: bool,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        Self {
+            speed: 20.0,
+            duration: 0.2,
+            cooldown_duration: 0.5,
+            ground_only: false,
+            ignore_gravity: true,
+            dash_timer: 0.0,
+            cooldown_timer: 0.0,
+            direction: Vec3::ZERO,
+            pressed_last_frame: false,
+        }
+    }
+}
+
+impl Dash {
+    /// Tick down timers by `dt`/delta time.
+    pub fn tick_timers(&mut self, dt: f32) {
+        self.dash_timer = (self.dash_timer - dt).max(0.0);
+        self.cooldown_timer = (self.cooldown_timer - dt).max(0.0);
+    }
+
+    /// Are we currently dashing?
+    pub fn dashing(&self) -> bool {
+        self.dash_timer > 0.0
+    }
+
+    /// Are we waiting for the dash cooldown to finish?
+    pub fn on_cooldown(&self) -> bool {
+        self.cooldown_timer > 0.0
+    }
+
+    /// Can we start a dash right now?
+    pub fn can_dash(&self, grounded: bool) -> bool {
+        !self.dashing() && !self.on_cooldown() && (grounded || !self.ground_only)
+    }
+
+    /// Start dashing in `direction`.
+    pub fn start(&mut self, direction: Vec3) {
+        self.direction = direction;
+        self.dash_timer = self.duration;
+        self.cooldown_timer = self.duration + self.cooldown_duration;
+    }
+}
+
+/// Calculated force for dashing.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct DashForce {
+    /// Linear force to keep the controller dashing.
+    pub linear: Vec3,
+}
+
+/// Components needed for a controller to dash.
+#[derive(Bundle, Default)]
+pub struct DashBundle {
+    /// How the controller's dashing should behave.
+    pub dash: Dash,
+    /// Calculated force for dashing.
+    pub dash_force: DashForce,
+}
+
+/// Sent when a controller starts a dash.
+#[derive(Event, Debug, Clone)]
+pub struct DashEvent {
+    /// The controller that dashed.
+    pub entity: Entity,
+    /// Direction of the dash.
+    pub direction: Vec3,
+}
+
+/// Dashing controllers and the forces a dash overrides.
+type DashQuery = (
+    Entity,
+    &'static mut DashForce,
+    &'static mut Dash,
+    &'static mut MovementForce,
+    &'static mut GravityForce,
+    &'static ControllerInput,
+    &'static GlobalTransform,
+    &'static Gravity,
+    &'static Upright,
+    &'static Grounded,
+    &'static ControllerVelocity,
+    &'static ControllerMass,
+);
+
+/// Calculate the dash force for the controller, overriding movement (and optionally gravity) while dashing.
+pub fn dash_force(
+    mut query: Query<DashQuery, Simulated>,
+    ctx: Res<RapierContext>,
+    mut dash_events: EventWriter<DashEvent>,
+) {
+    let dt = ctx.integration_parameters.dt;
+    for (
+        entity,
+        mut force,
+        mut dash,
+        mut movement_force,
+        mut gravity_force,
+        input,
+        tf,
+        gravity,
+        upright,
+        grounded,
+        velocity,
+        mass,
+    ) in &mut query
+    {
+        force.linear = Vec3::ZERO;
+        dash.tick_timers(dt);
+
+        let pressed = input.dashing && !dash.pressed_last_frame;
+        dash.pressed_last_frame = input.dashing;
+
+        if pressed && dash.can_dash(**grounded) {
+            let facing = upright.forward_vector.unwrap_or(tf.back());
+            let direction = gravity
+                .project(input.movement)
+                .try_normalize()
+                .or_else(|| gravity.project(facing).try_normalize());
+
+            if let Some(direction) = direction {
+                dash.start(direction);
+                dash_events.send(DashEvent { entity, direction });
+            }
+        }
+
+        if !dash.dashing() {
+            continue;
+        }
+
+        let goal = dash.direction * dash.speed;
+        let current = if dash.ignore_gravity {
+            gravity_force.linear = Vec3::ZERO;
+            velocity.linear
+        } else {
+            gravity.project(velocity.linear)
+        };
+
+        movement_force.linear = Vec3::ZERO;
+        force.linear = (goal - current) * mass.mass / dt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cooldown_starts_with_the_dash() {
+        let mut dash = Dash::default();
+        assert!(dash.can_dash(false));
+
+        dash.start(Vec3::X);
+        assert!(dash.dashing());
+        assert!(!dash.can_dash(true));
+
+        // The dash is over, but the cooldown runs on from its end.
+        dash.tick_timers(dash.duration);
+        assert!(!dash.dashing());
+        assert!(dash.on_cooldown());
+        assert!(!dash.can_dash(true));
+
+        dash.tick_timers(dash.cooldown_duration);
+        assert!(!dash.on_cooldown());
+        assert!(dash.can_dash(true));
+    }
+
+    #[test]
+    fn direction_is_locked_for_the_whole_dash() {
+        let mut dash = Dash::default();
+        dash.start(Vec3::NEG_Z);
+
+        // Another press mid-dash can't start a new dash in a new direction.
+        for _ in 0..10 {
+            dash.tick_timers(dash.duration / 20.0);
+            assert!(dash.dashing());
+            assert!(!dash.can_dash(true));
+            assert_eq!(dash.direction, Vec3::NEG_Z);
+        }
+    }
+
+    #[test]
+    fn ground_only_dash_needs_ground() {
+        let dash = Dash {
+            ground_only: true,
+            ..default()
+        };
+        assert!(!dash.can_dash(false));
+        assert!(dash.can_dash(true));
+    }
+}
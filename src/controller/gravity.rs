@@ -3,6 +3,11 @@ use crate::controller::*;
 /// How strong is the gravity for this controller.
 #[derive(Component, Reflect)]
 #[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Gravity {
     /// Acceleration in the `up_vector` direction due to gravity.
     ///
@@ -12,8 +17,42 @@ pub struct Gravity {
     pub acceleration: f32,
     /// Direction we should float up from.
     ///
-    /// The default is `Vec3::Y`.
+    /// The default is `Vec3::Y`. This is recalculated every frame for [`GravityMode::Point`].
     pub up_vector: Vec3,
+    /// Up vector to rotate `up_vector` towards, see [`Gravity::set_up_vector`].
+    pub target_up_vector: Option<Vec3>,
+    /// Max speed, in radians per second, that `up_vector` rotates towards `target_up_vector`.
+    ///
+    /// Keep this above the fastest continuous change in up vector (e.g. running around
+    /// a small planet) so it doesn't lag behind. `None` snaps to the target immediately.
+    pub max_up_rotation_speed: Option<f32>,
+    /// Which direction gravity pulls in.
+    pub mode: GravityMode,
+    /// Scale gravity by this near the apex of a jump/fall, giving a bit of hang-time.
+    ///
+    /// The default is `1.0`, which doesn't change gravity at all.
+    pub apex_gravity_scale: f32,
+    /// How slow, in the `up_vector` direction, the controller has to be moving for
+    /// [`apex_gravity_scale`](Self::apex_gravity_scale) to kick in.
+    ///
+    /// The scale ramps in smoothly the closer the vertical velocity is to zero.
+    pub apex_velocity_threshold: f32,
+    /// Scale gravity by this while airborne and moving up, e.g. lower for a floatier rise.
+    pub rise_gravity_scale: f32,
+    /// Scale gravity by this while airborne and moving down, e.g. higher for a snappier fall.
+    pub fall_gravity_scale: f32,
+    /// Vertical speed over which gravity blends between `fall_gravity_scale` and `rise_gravity_scale`
+    /// around zero, so the switch at the top of a jump isn't a sudden jolt.
+    pub rise_fall_blend: f32,
+    /// Max speed the controller can fall at, along the up vector.
+    ///
+    /// Horizontal velocity isn't affected. `None` lets the controller fall as fast as gravity takes it.
+    pub terminal_velocity: Option<f32>,
+    /// Gameplay limit on how fast the controller can fall, along the up vector.
+    ///
+    /// Unlike `terminal_velocity` this clamps the velocity after every controller force is added
+    /// together, so nothing the controller does can take it past this. `None` doesn't clamp.
+    pub max_fall_speed: Option<f32>,
 }
 
 impl Default for Gravity {
@@ -21,6 +60,116 @@ impl Default for Gravity {
         Gravity {
             acceleration: -9.817,
             up_vector: Vec3::Y,
+            target_up_vector: None,
+            max_up_rotation_speed: None,
+            mode: GravityMode::Directional,
+            apex_gravity_scale: 1.0,
+            apex_velocity_threshold: 2.0,
+            rise_gravity_scale: 1.0,
+            fall_gravity_scale: 1.0,
+            rise_fall_blend: 0.5,
+            terminal_velocity: None,
+            max_fall_speed: None,
+        }
+    }
+}
+
+/// Which direction gravity pulls in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GravityMode {
+    /// Always pull opposite of [`Gravity::up_vector`].
+    #[default]
+    Directional,
+    /// Pull towards a point, e.g. the center of a planet.
+    ///
+    /// [`Gravity::up_vector`] is updated to point away from the center.
+    Point {
+        /// Point gravity pulls towards, in world space.
+        center: Vec3,
+    },
+}
+
+/// A collider (usually a [`Sensor`]) that overrides the [`Gravity`] of controllers
+/// overlapping it, e.g. reversed or low gravity rooms.
+///
+/// Only controllers with a [`GravityVolumeState`] are affected.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct GravityVolume {
+    /// [`Gravity::acceleration`] to use inside of this volume.
+    pub acceleration: f32,
+    /// [`Gravity::up_vector`] to use inside of this volume.
+    pub up_vector: Vec3,
+    /// [`Gravity::mode`] to use inside of this volume.
+    pub mode: GravityMode,
+    /// [`Gravity::max_fall_speed`] to use inside of this volume, `None` lets controllers free fall.
+    pub max_fall_speed: Option<f32>,
+    /// When overlapping multiple volumes, the one with the highest priority is used.
+    pub priority: i32,
+}
+
+impl Default for GravityVolume {
+    fn default() -> Self {
+        Self {
+            acceleration: -9.817,
+            up_vector: Vec3::Y,
+            mode: GravityMode::Directional,
+            max_fall_speed: None,
+            priority: 0,
+        }
+    }
+}
+
+/// Which [`GravityVolume`] is affecting a controller, and the gravity to restore after leaving it.
+///
+/// This isn't part of [`Controller`], add it to controllers that should be affected by gravity volumes.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct GravityVolumeState {
+    /// Volume currently overriding the controller's gravity.
+    pub volume: Option<Entity>,
+    /// [`Gravity::acceleration`] from before entering the volume.
+    pub base_acceleration: f32,
+    /// [`Gravity::up_vector`] from before entering the volume.
+    pub base_up_vector: Vec3,
+    /// [`Gravity::mode`] from before entering the volume.
+    pub base_mode: GravityMode,
+    /// [`Gravity::max_fall_speed`] from before entering the volume.
+    pub base_max_fall_speed: Option<f32>,
+}
+
+/// Reorient gravity to the surface the controller is standing on, letting it walk up walls and
+/// onto ceilings.
+///
+/// While grounded [`Gravity::up_vector`] follows the ground normal, so gravity, floating,
+/// [`Upright`] and ground casting all use the surface as up. Set [`Gravity::max_up_rotation_speed`]
+/// to smooth out the reorientation. Gravity reverts to `base_up_vector` after being airborne for
+/// `revert_delay`. This overrides [`GravityMode::Point`] and [`GravityVolume`] up vectors.
+///
+/// This isn't part of [`Controller`], add it to controllers that should stick to any surface.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct SurfaceGravity {
+    /// How long the controller can be airborne before reverting to `base_up_vector`.
+    pub revert_delay: f32,
+    /// Up vector to revert to once airborne for too long.
+    pub base_up_vector: Vec3,
+    /// Climb onto walls the controller is moving into, needs a [`WallCaster`].
+    ///
+    /// Without this only ground the [`GroundCaster`] finds is followed, e.g. gradually curving ramps.
+    pub climb_walls: bool,
+    /// Timer for tracking `revert_delay`.
+    pub airborne_timer: f32,
+}
+
+impl Default for SurfaceGravity {
+    fn default() -> Self {
+        Self {
+            revert_delay: 0.5,
+            base_up_vector: Vec3::Y,
+            climb_walls: true,
+            airborne_timer: 0.0,
         }
     }
 }
@@ -33,9 +182,356 @@ pub struct GravityForce {
     pub linear: Vec3,
 }
 
+impl Gravity {
+    /// Project `vector` onto the plane perpendicular to the up vector.
+    pub fn project(&self, vector: Vec3) -> Vec3 {
+        vector - self.up_vector * vector.dot(self.up_vector)
+    }
+
+    /// Rotation from `Vec3::Y` to the up vector.
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_arc(Vec3::Y, self.up_vector)
+    }
+
+    /// Change the up vector, rotating towards it over time if `max_up_rotation_speed` is set.
+    pub fn set_up_vector(&mut self, up_vector: Vec3) {
+        self.target_up_vector = Some(up_vector);
+    }
+
+    /// Rotate `up_vector` towards `target_up_vector`.
+    pub fn update_up_vector(&mut self, dt: f32) {
+        let Some(target) = self.target_up_vector else { return };
+
+        let angle = self.up_vector.angle_between(target);
+        match self.max_up_rotation_speed {
+            Some(speed) if angle > speed * dt => {
+                let rotation = Quat::from_rotation_arc(self.up_vector, target);
+                let step = Quat::IDENTITY.slerp(rotation, speed * dt / angle);
+                self.up_vector = (step * self.up_vector).normalize();
+            }
+            _ => {
+                self.up_vector = target;
+                self.target_up_vector = None;
+            }
+        }
+    }
+
+    /// Gravity scale to use at the current vertical velocity while airborne, blending
+    /// between [`fall_gravity_scale`](Self::fall_gravity_scale) and [`rise_gravity_scale`](Self::rise_gravity_scale).
+    pub fn rise_fall_scale(&self, up_velocity: f32) -> f32 {
+        let t = if self.rise_fall_blend > 0.0 {
+            let t = (up_velocity / self.rise_fall_blend * 0.5 + 0.5).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        } else if up_velocity > 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+
+        self.fall_gravity_scale + (self.rise_gravity_scale - self.fall_gravity_scale) * t
+    }
+
+    /// Extra force needed to keep `force` from making the controller fall faster than `max_fall_speed`.
+    pub fn max_fall_speed_force(&self, velocity: Vec3, force: Vec3, mass: f32, dt: f32) -> Vec3 {
+        let Some(max_fall_speed) = self.max_fall_speed else { return Vec3::ZERO };
+        if dt == 0.0 {
+            return Vec3::ZERO;
+        }
+
+        let next_velocity = velocity + force * dt / mass;
+        let excess = -next_velocity.dot(self.up_vector) - max_fall_speed.max(0.0);
+        if excess > 0.0 {
+            self.up_vector * excess * mass / dt
+        } else {
+            Vec3::ZERO
+        }
+    }
+
+    /// Gravity scale to use at the current vertical velocity while airborne.
+    pub fn apex_scale(&self, up_velocity: f32) -> f32 {
+        if self.apex_velocity_threshold <= 0.0 {
+            return 1.0;
+        }
+
+        let t = (up_velocity.abs() / self.apex_velocity_threshold).min(1.0);
+        self.apex_gravity_scale + (1.0 - self.apex_gravity_scale) * t
+    }
+}
+
+/// Override the gravity of controllers overlapping a [`GravityVolume`].
+pub fn gravity_volumes(
+    mut controllers: Query<(
+        Entity,
+        &GlobalTransform,
+        &Collider,
+        &mut Gravity,
+        &mut GravityVolumeState,
+    )>,
+    volumes: Query<&GravityVolume>,
+    ctx: Res<RapierContext>,
+) {
+    for (entity, tf, collider, mut gravity, mut state) in &mut controllers {
+        let (_, rotation, position) = tf.to_scale_rotation_translation();
+        let predicate = |hit| hit != entity && volumes.contains(hit);
+        let filter = QueryFilter::new().predicate(&predicate);
+
+        let mut active: Option<(Entity, &GravityVolume)> = None;
+        ctx.intersections_with_shape(position, rotation, collider, filter, |hit| {
+            if let Ok(volume) = volumes.get(hit) {
+                let outranked =
+                    active.is_some_and(|(_, current)| current.priority >= volume.priority);
+                if !outranked {
+                    active = Some((hit, volume));
+                }
+            }
+            true
+        });
+
+        match active {
+            Some((volume_entity, volume)) => {
+                if state.volume.is_none() {
+                    state.base_acceleration = gravity.acceleration;
+                    state.base_up_vector = gravity.target_up_vector.unwrap_or(gravity.up_vector);
+                    state.base_mode = gravity.mode;
+                    state.base_max_fall_speed = gravity.max_fall_speed;
+                }
+
+                state.volume = Some(volume_entity);
+                gravity.acceleration = volume.acceleration;
+                gravity.set_up_vector(volume.up_vector);
+                gravity.mode = volume.mode;
+                gravity.max_fall_speed = volume.max_fall_speed;
+            }
+            None => {
+                if state.volume.take().is_some() {
+                    gravity.acceleration = state.base_acceleration;
+                    gravity.set_up_vector(state.base_up_vector);
+                    gravity.mode = state.base_mode;
+                    gravity.max_fall_speed = state.base_max_fall_speed;
+                }
+            }
+        }
+    }
+}
+
+/// Controllers that stick to the surface they are standing or climbing on.
+type SurfaceGravityQuery = (
+    &'static mut SurfaceGravity,
+    &'static mut Gravity,
+    &'static Grounded,
+    &'static ViableGroundCast,
+    Option<&'static WallCast>,
+    &'static ControllerInput,
+);
+
+/// Point the up vector of controllers with [`SurfaceGravity`] away from the surface they're on.
+pub fn surface_gravity(mut query: Query<SurfaceGravityQuery>, ctx: Res<RapierContext>) {
+    let dt = ctx.integration_parameters.dt;
+    for (mut surface, mut gravity, grounded, ground, wall, input) in &mut query {
+        if !**grounded {
+            surface.airborne_timer += dt;
+            if surface.airborne_timer >= surface.revert_delay {
+                gravity.set_up_vector(surface.base_up_vector);
+            }
+            continue;
+        }
+
+        surface.airborne_timer = 0.0;
+
+        // Only climb walls we're pushing into, not ones we're running alongside.
+        let wall = wall
+            .and_then(|wall| **wall)
+            .filter(|wall| surface.climb_walls && input.movement.dot(-wall.cast.normal) > 0.5);
+        let normal = match wall {
+            Some(wall) => wall.cast.normal,
+            None => {
+                let Some(ground) = ground.current() else { continue };
+                ground.cast.normal
+            }
+        };
+
+        if let Some(normal) = normal.try_normalize() {
+            gravity.set_up_vector(normal);
+        }
+    }
+}
+
+/// Update the up vector of controllers, following [`GravityMode::Point`] gravity
+/// and rotating towards [`Gravity::target_up_vector`].
+pub fn update_gravity(mut query: Query<(&GlobalTransform, &mut Gravity)>, ctx: Res<RapierContext>) {
+    let dt = ctx.integration_parameters.dt;
+    for (tf, mut gravity) in &mut query {
+        if let GravityMode::Point { center } = gravity.mode {
+            if let Some(up) = (tf.translation() - center).try_normalize() {
+                gravity.set_up_vector(up);
+            }
+        }
+
+        gravity.update_up_vector(dt);
+    }
+}
+
+/// State used to calculate [`GravityForce`].
+type GravityForceQuery = (
+    &'static mut GravityForce,
+    &'static Gravity,
+    &'static ControllerMass,
+    &'static ControllerVelocity,
+    &'static Grounded,
+    Option<&'static NoClip>,
+);
+
 /// Calculate gravity force.
-pub fn gravity_force(mut query: Query<(&mut GravityForce, &Gravity, &ControllerMass)>) {
-    for (mut force, gravity, mass) in &mut query {
-        force.linear = gravity.up_vector * mass.mass * gravity.acceleration;
+pub fn gravity_force(mut query: Query<GravityForceQuery>, ctx: Res<RapierContext>) {
+    let dt = ctx.integration_parameters.dt;
+    query
+        .par_iter_mut()
+        .for_each_mut(|(mut force, gravity, mass, velocity, grounded, no_clip)| {
+            if no_clip.is_some() {
+                force.linear = Vec3::ZERO;
+                return;
+            }
+
+            let scale = if **grounded {
+                1.0
+            } else {
+                let up_velocity = velocity.vertical_speed(gravity.up_vector);
+                gravity.apex_scale(up_velocity) * gravity.rise_fall_scale(up_velocity)
+            };
+
+            let acceleration = gravity.acceleration * scale;
+            force.linear = gravity.up_vector * mass.mass * acceleration;
+
+            if let Some(terminal_velocity) = gravity.terminal_velocity {
+                // Hold back whatever would take us past terminal velocity.
+                let fall_speed = -velocity.vertical_speed(gravity.up_vector) - acceleration * dt;
+                let excess = fall_speed - terminal_velocity.max(0.0);
+                if excess > 0.0 {
+                    force.linear += gravity.up_vector * excess * mass.mass / dt;
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rise_fall_scale_blends_around_apex() {
+        let gravity = Gravity {
+            rise_gravity_scale: 0.5,
+            fall_gravity_scale: 2.0,
+            rise_fall_blend: 0.5,
+            ..default()
+        };
+
+        assert_eq!(gravity.rise_fall_scale(0.5), 0.5);
+        assert_eq!(gravity.rise_fall_scale(10.0), 0.5);
+        assert_eq!(gravity.rise_fall_scale(-0.5), 2.0);
+        assert_eq!(gravity.rise_fall_scale(-10.0), 2.0);
+        assert!((gravity.rise_fall_scale(0.0) - 1.25).abs() < 1e-5);
+
+        // Smoothly goes from the fall scale to the rise scale.
+        let mut last = gravity.rise_fall_scale(-0.5);
+        for step in 1..=20 {
+            let scale = gravity.rise_fall_scale(-0.5 + step as f32 * 0.05);
+            assert!(scale <= last);
+            last = scale;
+        }
+    }
+
+    #[test]
+    fn rise_fall_scale_without_blend_switches() {
+        let gravity = Gravity {
+            rise_gravity_scale: 0.5,
+            fall_gravity_scale: 2.0,
+            rise_fall_blend: 0.0,
+            ..default()
+        };
+
+        assert_eq!(gravity.rise_fall_scale(0.01), 0.5);
+        assert_eq!(gravity.rise_fall_scale(0.0), 2.0);
+        assert_eq!(gravity.rise_fall_scale(-0.01), 2.0);
+    }
+
+    #[test]
+    fn max_fall_speed_caps_downward_speed() {
+        let gravity = Gravity {
+            max_fall_speed: Some(10.0),
+            ..default()
+        };
+        let (mass, dt) = (2.0, 0.1);
+        let velocity = Vec3::NEG_Y * 10.0;
+        let force = gravity.up_vector * gravity.acceleration * mass;
+
+        let extra = gravity.max_fall_speed_force(velocity, force, mass, dt);
+        let next_velocity = velocity + (force + extra) * dt / mass;
+        assert!(extra.dot(gravity.up_vector) > 0.0);
+        assert!((next_velocity.y + 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn max_fall_speed_ignores_slower_falls() {
+        let gravity = Gravity {
+            max_fall_speed: Some(10.0),
+            ..default()
+        };
+        let force = gravity.up_vector * gravity.acceleration;
+
+        // Rising or falling slower than the cap is left alone, as is sideways speed.
+        for velocity in [Vec3::Y * 5.0, Vec3::NEG_Y * 2.0, Vec3::X * 50.0] {
+            let extra = gravity.max_fall_speed_force(velocity, force, 1.0, 0.1);
+            assert_eq!(extra, Vec3::ZERO);
+        }
+
+        let uncapped = Gravity::default();
+        let extra = uncapped.max_fall_speed_force(Vec3::NEG_Y * 100.0, force, 1.0, 0.1);
+        assert_eq!(extra, Vec3::ZERO);
+    }
+
+    #[test]
+    fn max_fall_speed_follows_up_vector() {
+        let up_vector = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let gravity = Gravity {
+            up_vector,
+            max_fall_speed: Some(5.0),
+            ..default()
+        };
+        let velocity = -up_vector * 8.0 + Vec3::Z * 3.0;
+
+        let extra = gravity.max_fall_speed_force(velocity, Vec3::ZERO, 1.0, 0.5);
+        let next_velocity = velocity + extra * 0.5;
+        assert!((next_velocity.dot(up_vector) + 5.0).abs() < 1e-4);
+        assert!((next_velocity.z - 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn forces_for_a_crowd_of_controllers() {
+        use bevy::tasks::{ComputeTaskPool, TaskPool};
+
+        ComputeTaskPool::init(TaskPool::new);
+        let mut world = World::new();
+        world.insert_resource(RapierContext::default());
+        let mut schedule = Schedule::default();
+        schedule.add_systems((gravity_force, accumulate_forces).chain());
+
+        let controllers = (0..1000)
+            .map(|index| {
+                let mut controller = crate::ControllerBundle::default();
+                controller.physics.mass.mass = 1.0 + index as f32 * 0.01;
+                world.spawn(controller).id()
+            })
+            .collect::<Vec<_>>();
+        schedule.run(&mut world);
+
+        // Every controller gets its own gravity, nothing is mixed up between them.
+        for entity in controllers {
+            let mass = world.get::<ControllerMass>(entity).unwrap().mass;
+            let gravity = world.get::<Gravity>(entity).unwrap();
+            let expected = gravity.up_vector * gravity.acceleration * mass;
+            let force = world.get::<ControllerForce>(entity).unwrap();
+            assert!((force.linear - expected).length() < 1e-3);
+        }
     }
 }
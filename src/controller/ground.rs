@@ -1,5 +1,8 @@
 use crate::controller::*;
-use bevy::utils::HashSet;
+use bevy::{
+    ecs::{query::WorldQuery, system::StaticSystemParam},
+    utils::HashSet,
+};
 use bevy_rapier3d::{
     na::Isometry3,
     parry::{
@@ -8,18 +11,56 @@ use bevy_rapier3d::{
     },
     rapier::geometry::ContactManifold,
 };
+use std::ops::RangeInclusive;
 
 /// How to detect if something below the controller is suitable
 /// for standing on.
 #[derive(Component, Reflect)]
 #[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct GroundCaster {
     /// A timer to track how long to skip the ground check for (see [`jump_skip_ground_check_duration`](ControllerSettings::jump_skip_ground_check_duration)).
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub skip_ground_check_timer: f32,
     /// Override skip ground check. If true, never checks for the ground.
     pub skip_ground_check_override: bool,
+    /// How long the controller has to be off the ground before a [`LeftGroundEvent`] is sent.
+    ///
+    /// This keeps brief flickers in groundedness from sending [`LeftGroundEvent`]/[`LandedEvent`]s.
+    pub event_debounce_duration: f32,
+    /// How long the controller has been off the ground.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub airborne_timer: f32,
+    /// Has a [`LeftGroundEvent`] been sent since the controller was last grounded.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub left_ground_sent: bool,
+    /// How long the ground has to be in reach before the controller becomes [`Grounded`].
+    pub ground_delay: f32,
+    /// How long the ground has to be out of reach before the controller stops being [`Grounded`].
+    ///
+    /// This smooths over small gaps, unlike [`Jump::coyote_duration`] it keeps the controller
+    /// grounded for everything else too (e.g. float and movement). Jumping skips this.
+    pub air_delay: f32,
+    /// How long the ground being in reach has disagreed with [`Grounded`], for `ground_delay`/`air_delay`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub groundedness_timer: f32,
     /// An offset to start the ground check from, relative to the character's origin.
+    ///
+    /// For off-center colliders, set [`Float::anchor_offset`] to the same point so the float
+    /// distance is measured from where the cast starts.
     pub cast_origin: Vec3,
+    /// Up vector to use for ground casting and groundedness instead of [`Gravity::up_vector`],
+    /// e.g. for walking on walls while gravity still pulls down.
+    ///
+    /// The ground is cast for in the opposite direction, and ground angles are measured against it.
+    /// `None` follows the gravity's up vector. Gravity, floating and [`Upright`] still use
+    /// [`Gravity::up_vector`], so to stand upright on the ground being cast against change the
+    /// gravity's up vector as well.
+    pub up_vector: Option<Vec3>,
     /// How long of a ray to cast to detect the ground. Setting this unnecessarily high will permanently count the player as grounded,
     /// and too low will allow the player to slip and become disconnected from the ground easily.
     pub cast_length: f32,
@@ -27,9 +68,38 @@ pub struct GroundCaster {
     ///
     /// The default is the controller's base collider.
     #[reflect(ignore)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub cast_collider: Option<Collider>,
+    /// Whether to cast the shape or a single ray, see [`GroundCastKind`].
+    pub cast_kind: GroundCastKind,
+    /// How quickly the ground normal follows changes while staying grounded, higher is faster.
+    ///
+    /// This smooths out jumps between the faces of tessellated terrain. The normal
+    /// snaps immediately when landing. `0.0` disables smoothing.
+    pub normal_smoothing: f32,
+    /// Extra points, relative to `cast_origin` in the controller's local space, to also cast from.
+    ///
+    /// The closest hit is used as the ground with the normals of all hits averaged, so standing
+    /// partially over a ledge stays grounded as long as any of the points finds ground.
+    /// See [`GroundCaster::ring_sample_points`].
+    pub sample_points: Vec<Vec3>,
     /// Set of entities that should be ignored when ground casting.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub exclude_from_ground: HashSet<Entity>,
+    /// Collision groups of colliders that can be considered ground.
+    ///
+    /// The default considers everything to be ground.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub ground_groups: CollisionGroups,
+    /// Reuse the last ground cast until the controller has moved this far from where it was cast.
+    ///
+    /// Casts are always redone if the ground is moving or the up vector changes. This is useful
+    /// for large crowds of mostly idle controllers. `None` casts every frame.
+    pub recast_threshold: Option<f32>,
+    /// Position and up vector of the last ground cast, see `recast_threshold`.
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub last_cast: Option<(Vec3, Vec3)>,
 
     /// Threshold, in radians, of when a controller will start to slip on a surface.
     ///
@@ -39,6 +109,9 @@ pub struct GroundCaster {
     ///
     /// For example, if this is set to `π/4` (45 degrees), then a controller standing on a slope steeper than 45 degrees will slip and fall, and will not have
     /// their jump refreshed by landing on that surface.
+    ///
+    /// The angle is measured between the ground normal and the ground casting up vector,
+    /// see [`GroundCaster::up_vector`](Self::up_vector()).
     pub max_ground_angle: f32,
 }
 
@@ -47,16 +120,88 @@ impl Default for GroundCaster {
         Self {
             skip_ground_check_timer: 0.0,
             skip_ground_check_override: false,
+            event_debounce_duration: 0.05,
+            airborne_timer: 0.0,
+            left_ground_sent: false,
+            ground_delay: 0.0,
+            air_delay: 0.0,
+            groundedness_timer: 0.0,
             cast_origin: Vec3::ZERO,
+            up_vector: None,
             cast_length: 1.05,
             cast_collider: None,
+            cast_kind: GroundCastKind::Shape,
+            normal_smoothing: 0.0,
+            sample_points: Vec::new(),
             exclude_from_ground: default(),
+            ground_groups: CollisionGroups::default(),
+            recast_threshold: None,
+            last_cast: None,
             unstable_ground_angle: 45.0 * (std::f32::consts::PI / 180.0),
             max_ground_angle: 60.0 * (std::f32::consts::PI / 180.0),
         }
     }
 }
 
+impl GroundCaster {
+    /// Up vector used for ground casting, the `up_vector` field or [`Gravity::up_vector`].
+    pub fn up_vector(&self, gravity: &Gravity) -> Vec3 {
+        self.up_vector
+            .and_then(Vec3::try_normalize)
+            .unwrap_or(gravity.up_vector)
+    }
+
+    /// Can the last ground cast be reused instead of casting again, see [`GroundCaster::recast_threshold`].
+    pub fn can_reuse_cast(&self, position: Vec3, up_vector: Vec3, ground: &GroundCache) -> bool {
+        let Some(threshold) = self.recast_threshold else { return false };
+        let Some((last_position, last_up)) = self.last_cast else { return false };
+
+        let moving_ground = ground.current().is_some_and(|ground| {
+            ground.point_velocity != Vec3::ZERO || ground.angular_velocity != Vec3::ZERO
+        });
+
+        !moving_ground && last_up == up_vector && last_position.distance(position) < threshold
+    }
+
+    /// Ease from the `previous` ground normal towards `normal` based on [`GroundCaster::normal_smoothing`].
+    pub fn smooth_normal(&self, previous: Vec3, normal: Vec3, dt: f32) -> Vec3 {
+        if self.normal_smoothing <= 0.0 || previous.length_squared() == 0.0 {
+            return normal;
+        }
+
+        let t = 1.0 - (-self.normal_smoothing * dt).exp();
+        previous.lerp(normal, t).try_normalize().unwrap_or(normal)
+    }
+
+    /// `count` evenly spaced points in a circle of `radius` around the local up axis,
+    /// for use in [`GroundCaster::sample_points`].
+    pub fn ring_sample_points(count: usize, radius: f32) -> Vec<Vec3> {
+        (0..count)
+            .map(|index| {
+                let angle = index as f32 / count as f32 * std::f32::consts::TAU;
+                Vec3::new(angle.cos(), 0.0, angle.sin()) * radius
+            })
+            .collect()
+    }
+}
+
+/// How to cast for the ground.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroundCastKind {
+    /// Cast the collider shape (see [`GroundCaster::cast_collider`], e.g. a [`Collider::ball`] for a
+    /// specific radius), falling back to a ray if the shape is stuck in the ground.
+    ///
+    /// This catches the edges of ledges and thin platforms the center of the controller isn't over.
+    #[default]
+    Shape,
+    /// Only cast a single ray from the bottom of the collider.
+    ///
+    /// This is cheaper, but misses ground that isn't directly beneath the controller,
+    /// so the controller will fall off of ledges as soon as its center passes the edge.
+    Ray,
+}
+
 /// Information about the ground entity/where we are touching it.
 #[derive(Copy, Clone)]
 pub struct Ground {
@@ -72,44 +217,20 @@ pub struct Ground {
     pub angular_velocity: Vec3,
     /// Linear velocity of the ground body.
     pub linear_velocity: Vec3,
-    /// Linear velocity at the point of contact.
+    /// Linear velocity at the point of contact, including the ground's rotation (`linear + angular × r`).
+    ///
+    /// This is what the controller is carried with and moves relative to.
     pub point_velocity: Vec3,
 }
 
 impl Ground {
-    /// Construct a `Ground` based on the results of `GroundCastParams`.
+    /// Construct a `Ground` based on the results of `GroundCastParams`, standing on `body`.
     pub fn from_cast(
-        entity: Entity,
         cast: CastResult,
         up_vector: Vec3,
         caster: &GroundCaster,
-        ctx: &RapierContext,
-        masses: &Query<&ReadMassProperties>,
-        velocities: &Query<&Velocity>,
-        globals: &Query<&GlobalTransform>,
+        body: PointVelocity,
     ) -> Self {
-        let ground_entity = ctx.collider_parent(entity).unwrap_or(entity);
-
-        let mass = if let Ok(mass) = masses.get(ground_entity) {
-            mass.0.clone()
-        } else {
-            MassProperties::default()
-        };
-
-        let local_com = mass.local_center_of_mass;
-
-        let ground_velocity = velocities
-            .get(ground_entity)
-            .copied()
-            .unwrap_or(Velocity::default());
-
-        let global = globals
-            .get(ground_entity)
-            .unwrap_or(&GlobalTransform::IDENTITY);
-        let com = global.transform_point(local_com);
-        let point_velocity =
-            ground_velocity.linvel + ground_velocity.angvel.cross(cast.point - com);
-
         let (stable, viable) = if cast.normal.length() > 0.0 {
             let viable = cast.viable(up_vector, caster.max_ground_angle);
             let stable = cast.viable(up_vector, caster.unstable_ground_angle) && viable;
@@ -119,13 +240,13 @@ impl Ground {
         };
 
         Ground {
-            entity: ground_entity,
+            entity: body.body,
             cast: cast,
             stable: stable,
             viable: viable,
-            linear_velocity: ground_velocity.linvel,
-            angular_velocity: ground_velocity.angvel,
-            point_velocity: point_velocity,
+            linear_velocity: body.velocity.linear,
+            angular_velocity: body.velocity.angular,
+            point_velocity: body.point,
         }
     }
 }
@@ -190,6 +311,14 @@ impl GroundCache {
         }
     }
 
+    /// Mutable access to the ground we are currently touching.
+    pub fn current_mut(&mut self) -> Option<&mut Ground> {
+        match self {
+            Self::Ground(ground) => Some(ground),
+            _ => None,
+        }
+    }
+
     /// Last ground we touched, this includes the ground we are currently touching.
     pub fn last(&self) -> Option<&Ground> {
         match self {
@@ -199,10 +328,69 @@ impl GroundCache {
     }
 }
 
-/// Is the character grounded?
-#[derive(Component, Default, Reflect, Deref)]
+/// Where the controller is touching the ground, updated every frame.
+///
+/// This is only filled in while there is viable ground beneath the controller, see [`ViableGroundCast`].
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
 #[reflect(Component, Default)]
-pub struct Grounded(pub bool);
+pub struct GroundContact {
+    /// Ground entity, `None` if there is no viable ground.
+    pub entity: Option<Entity>,
+    /// Contact point on the ground.
+    pub point: Vec3,
+    /// Normal of the ground at the contact point.
+    pub normal: Vec3,
+    /// Distance from the controller to the contact point along the up vector.
+    pub distance: f32,
+    /// Velocity of the ground at the contact point.
+    pub surface_velocity: Vec3,
+}
+
+/// Convenience query for reading the ground state of a controller without
+/// needing to know about [`GroundCast`]/[`ViableGroundCast`].
+#[derive(WorldQuery)]
+pub struct GroundQuery {
+    /// Is the controller grounded.
+    pub grounded: &'static Grounded,
+    /// Viable ground found by the controller.
+    pub viable_ground: &'static ViableGroundCast,
+}
+
+impl<'w> GroundQueryItem<'w> {
+    /// Is the controller currently grounded?
+    pub fn is_grounded(&self) -> bool {
+        self.grounded.is_grounded()
+    }
+
+    /// The ground the controller is standing on, only if grounded.
+    ///
+    /// This includes the ground entity, contact point and normal.
+    pub fn ground(&self) -> Option<&'w Ground> {
+        if self.is_grounded() {
+            self.viable_ground.current()
+        } else {
+            None
+        }
+    }
+}
+
+/// Sent when a controller lands on the ground.
+#[derive(Event, Debug, Clone)]
+pub struct LandedEvent {
+    /// Controller that landed.
+    pub entity: Entity,
+    /// Speed the controller was moving towards the ground, relative to the ground, when it landed.
+    pub impact_velocity: f32,
+    /// Ground the controller landed on.
+    pub ground: Entity,
+}
+
+/// Sent when a controller leaves the ground, see [`GroundCaster::event_debounce_duration`].
+#[derive(Event, Debug, Clone)]
+pub struct LeftGroundEvent {
+    /// Controller that left the ground.
+    pub entity: Entity,
+}
 
 /// Force applied to the ground the controller is on.
 #[derive(Copy, Clone, Component, Default, Reflect)]
@@ -215,7 +403,7 @@ pub struct GroundForce {
 }
 
 /// Performs groundcasting and updates controller state accordingly.
-pub fn find_ground(
+pub fn find_ground<B: PhysicsBackend>(
     time: Res<Time>,
     mut casters: Query<(
         Entity,
@@ -226,80 +414,41 @@ pub fn find_ground(
         &mut ViableGroundCast,
     )>,
 
-    velocities: Query<&Velocity>,
-    masses: Query<&ReadMassProperties>,
-    globals: Query<&GlobalTransform>,
-    colliders: Query<&Collider>,
-
-    ctx: Res<RapierContext>,
-    mut gizmos: Gizmos,
+    backend: StaticSystemParam<B>,
+    #[cfg(feature = "debug_lines")] mut gizmos: Gizmos,
+    #[cfg(feature = "debug_lines")] debug: Res<crate::WanderlustDebugConfig>,
 ) {
-    let dt = ctx.integration_parameters.dt;
+    let dt = B::dt(&backend);
     if time.delta_seconds() == 0.0 {
         return;
     }
 
-    for (entity, tf, gravity, mut caster, mut ground, mut viable_ground) in &mut casters {
-        if caster.skip_ground_check_timer == 0.0 && !caster.skip_ground_check_override {
-            let cast_position = tf.transform_point(caster.cast_origin);
-            let cast_rotation = tf.to_scale_rotation_translation().1;
-            let cast_direction = -gravity.up_vector;
-            let Ok(caster_collider) = colliders.get(entity) else { continue };
-            let shape = caster.cast_collider.as_ref().unwrap_or(caster_collider);
-
-            let predicate =
-                |collider| collider != entity && !caster.exclude_from_ground.contains(&collider);
-            let filter = QueryFilter::new().exclude_sensors().predicate(&predicate);
-
-            let mut viable_params = GroundCastParams {
-                position: cast_position,
-                rotation: cast_rotation,
-                direction: cast_direction,
-                shape: &shape,
-                max_toi: caster.cast_length,
-                filter: filter,
-            };
+    #[cfg(feature = "debug_lines")]
+    let mut gizmos = debug.casts.then_some(&mut gizmos);
+    #[cfg(not(feature = "debug_lines"))]
+    let mut gizmos: Option<&mut Gizmos> = None;
 
-            let mut any_params = viable_params.clone();
-
-            let next_viable_ground = viable_params
-                .viable_cast_iters(
-                    &*ctx,
-                    &globals,
-                    caster.max_ground_angle,
-                    gravity.up_vector,
-                    5,
-                    &mut gizmos,
-                )
-                .map(|(entity, cast)| {
-                    Ground::from_cast(
-                        entity,
-                        cast,
-                        gravity.up_vector,
-                        &*caster,
-                        &*ctx,
-                        &masses,
-                        &velocities,
-                        &globals,
-                    )
-                });
+    for (entity, tf, gravity, mut caster, mut ground, mut viable_ground) in &mut casters {
+        let up_vector = caster.up_vector(gravity);
+        let reuse_cast = caster.can_reuse_cast(tf.translation(), up_vector, &ground);
+        if caster.skip_ground_check_timer == 0.0
+            && !caster.skip_ground_check_override
+            && !reuse_cast
+        {
+            let Some(hits) = B::cast_ground(&backend, entity, &caster, tf, up_vector, gizmos.as_deref_mut()) else { continue };
+
+            let next_viable_ground = hits.viable.map(|(entity, cast)| {
+                let body = B::point_velocity(&backend, entity, cast.point);
+                Ground::from_cast(cast, up_vector, &caster, body)
+            });
             viable_ground.update(next_viable_ground);
 
-            let next_ground = any_params
-                .cast_iters(&*ctx, &globals, gravity.up_vector, 5, &mut gizmos)
-                .map(|(entity, cast)| {
-                    Ground::from_cast(
-                        entity,
-                        cast,
-                        gravity.up_vector,
-                        &*caster,
-                        &*ctx,
-                        &masses,
-                        &velocities,
-                        &globals,
-                    )
-                });
+            let next_ground = hits.any.map(|(entity, cast)| {
+                let body = B::point_velocity(&backend, entity, cast.point);
+                Ground::from_cast(cast, up_vector, &caster, body)
+            });
             ground.update(next_ground);
+            caster.last_cast = Some((tf.translation(), up_vector));
         } else {
             caster.skip_ground_check_timer = (caster.skip_ground_check_timer - dt).max(0.0);
         };
@@ -353,33 +502,134 @@ pub fn find_ground(
         */
 
         // If we hit something, just get back up instead of waiting.
-        if ctx.contacts_with(entity).next().is_some() {
+        if B::in_contact(&backend, entity) {
             caster.skip_ground_check_timer = 0.0;
         }
     }
 }
 
+/// Ground found by [`cast_ground`].
+#[derive(Debug, Default, Copy, Clone)]
+pub struct GroundHits {
+    /// Closest ground found, this might not be viable for standing on.
+    pub any: Option<(Entity, CastResult)>,
+    /// Closest ground that is viable for standing on.
+    pub viable: Option<(Entity, CastResult)>,
+}
+
+/// Cast for the ground the same way [`find_ground`] does, without needing a controller.
+///
+/// - `params`: How to cast, `params.position` is replaced by each of the `origins`.
+///   The opposite of `params.direction` is used as the up vector for slope angles.
+/// - `origins`: World-space positions to cast from, e.g. the controller's position and
+///   any [`GroundCaster::sample_points`]. The hits from all origins are merged with
+///   [`merge_ground_casts`].
+/// - `max_ground_angle`: Steepest slope (in radians) considered viable ground.
+///
+/// Each cast corrects penetrations, averages the normal over a few nearby rays, and slides
+/// down non-viable slopes to look for viable ground beneath them. The casts are drawn to
+/// `gizmos` if given.
+pub fn cast_ground(
+    ctx: &RapierContext,
+    globals: &Query<&GlobalTransform>,
+    params: &GroundCastParams,
+    origins: &[Vec3],
+    max_ground_angle: f32,
+    mut gizmos: Option<&mut Gizmos>,
+) -> GroundHits {
+    let up_vector = -params.direction;
+    let mut viable_casts = Vec::new();
+    let mut any_casts = Vec::new();
+    for &position in origins {
+        let mut viable_params = GroundCastParams {
+            position,
+            ..params.clone()
+        };
+
+        let mut any_params = viable_params.clone();
+
+        viable_casts.extend(viable_params.viable_cast_iters(
+            ctx,
+            globals,
+            max_ground_angle,
+            up_vector,
+            5,
+            gizmos.as_deref_mut(),
+        ));
+        any_casts.extend(any_params.cast_iters(ctx, globals, up_vector, 5, gizmos.as_deref_mut()));
+    }
+
+    GroundHits {
+        any: merge_ground_casts(&any_casts),
+        viable: merge_ground_casts(&viable_casts),
+    }
+}
+
+/// Combine the ground casts from multiple sample points.
+///
+/// Uses the closest hit, with the normal averaged across all of the hits.
+pub fn merge_ground_casts(casts: &[(Entity, CastResult)]) -> Option<(Entity, CastResult)> {
+    let (entity, closest) = casts
+        .iter()
+        .copied()
+        .min_by(|(_, a), (_, b)| a.toi.total_cmp(&b.toi))?;
+
+    let normal = casts
+        .iter()
+        .map(|(_, cast)| cast.normal)
+        .sum::<Vec3>()
+        .try_normalize()
+        .unwrap_or(closest.normal);
+
+    Some((entity, CastResult { normal, ..closest }))
+}
+
+/// Ground state of controllers that is updated after casting for the ground.
+type GroundednessQuery = (
+    Entity,
+    &'static GlobalTransform,
+    &'static Gravity,
+    &'static Float,
+    &'static mut GroundCaster,
+    &'static mut ViableGroundCast,
+    &'static ControllerVelocity,
+    &'static mut Grounded,
+    &'static mut GroundContact,
+);
+
 /// Are we currently touching the ground with a fudge factor included.
 pub fn determine_groundedness(
-    mut query: Query<(
-        &GlobalTransform,
-        &Gravity,
-        &Float,
-        &ViableGroundCast,
-        &ControllerVelocity,
-        &mut Grounded,
-    )>,
+    mut query: Query<GroundednessQuery, Without<NoClip>>,
+    ctx: Res<RapierContext>,
+    mut landed_events: EventWriter<LandedEvent>,
+    mut left_ground_events: EventWriter<LeftGroundEvent>,
 ) {
-    for (global, gravity, float, viable_ground, velocity, mut grounded) in &mut query {
+    let dt = ctx.integration_parameters.dt;
+    for (
+        entity,
+        global,
+        gravity,
+        float,
+        mut caster,
+        mut viable_ground,
+        velocity,
+        mut grounded,
+        mut contact,
+    ) in &mut query
+    {
+        let up_vector = caster.up_vector(gravity);
+        let was_grounded = grounded.0;
+        let previous_normal = contact.normal;
+
         grounded.0 = false;
-        if let Some(ground) = viable_ground.current() {
-            let up_velocity = velocity.linear.dot(gravity.up_vector);
-            let translation = global.translation();
-            let updated_toi =
-                translation.dot(gravity.up_vector) - ground.cast.point.dot(gravity.up_vector);
+        *contact = GroundContact::default();
+        if let Some(ground) = viable_ground.current_mut() {
+            let up_velocity = velocity.linear.dot(up_vector);
+            let translation = float.anchor(global);
+            let updated_toi = translation.dot(up_vector) - ground.cast.point.dot(up_vector);
             //gizmos.sphere(ground.cast.point, Quat::IDENTITY, 0.3, Color::RED);
             //gizmos.sphere(translation, Quat::IDENTITY, 0.3, Color::GREEN);
-            let offset = float.distance - updated_toi;
+            let offset = float.current_distance() - updated_toi;
 
             //let up_velocity = up_velocity.clamp(-float.distance, float.distance);
             // Loosen constraints based on velocity.
@@ -393,7 +643,25 @@ pub fn determine_groundedness(
             } else {
                 float.min_offset
             };
-            grounded.0 = offset >= min && offset <= max;
+            grounded.0 = ground.cast.supports(
+                up_vector,
+                caster.max_ground_angle,
+                updated_toi,
+                offset,
+                min..=max,
+            );
+
+            if was_grounded && grounded.0 {
+                ground.cast.normal = caster.smooth_normal(previous_normal, ground.cast.normal, dt);
+            }
+
+            *contact = GroundContact {
+                entity: Some(ground.entity),
+                point: ground.cast.point,
+                normal: ground.cast.normal,
+                distance: updated_toi,
+                surface_velocity: ground.point_velocity,
+            };
             /*
             info!(
                 "grounded: {:?}, {:.3?} <= {:.3?} <= {:.3?}",
@@ -401,6 +669,53 @@ pub fn determine_groundedness(
             );
             */
         };
+
+        // Only flip groundedness once it has disagreed for long enough.
+        let in_reach = grounded.0;
+        let delay = if in_reach {
+            caster.ground_delay
+        } else if caster.skip_ground_check_timer > 0.0 {
+            0.0
+        } else {
+            caster.air_delay
+        };
+        if in_reach == was_grounded {
+            caster.groundedness_timer = 0.0;
+        } else {
+            caster.groundedness_timer += dt;
+            if caster.groundedness_timer < delay {
+                grounded.0 = was_grounded;
+            } else {
+                caster.groundedness_timer = 0.0;
+            }
+        }
+
+        match (grounded.0, contact.entity) {
+            // Still grounded over a gap, see `GroundCaster::air_delay`.
+            (true, None) => {}
+            (true, Some(ground)) => {
+                if caster.left_ground_sent {
+                    let relative_velocity = velocity.linear - contact.surface_velocity;
+                    landed_events.send(LandedEvent {
+                        entity,
+                        impact_velocity: -relative_velocity.dot(up_vector),
+                        ground,
+                    });
+                }
+
+                caster.airborne_timer = 0.0;
+                caster.left_ground_sent = false;
+            }
+            _ => {
+                caster.airborne_timer += dt;
+                if !caster.left_ground_sent
+                    && caster.airborne_timer >= caster.event_debounce_duration
+                {
+                    left_ground_events.send(LeftGroundEvent { entity });
+                    caster.left_ground_sent = true;
+                }
+            }
+        }
     }
 }
 
@@ -424,9 +739,36 @@ impl CastResult {
         -(projected_x + projected_z)
     }
 
+    /// Leeway, in radians, for surfaces at exactly the max angle in [`CastResult::viable`].
+    ///
+    /// Normals coming out of casts (and `angle_between`) are a little off, so a slope built at
+    /// exactly the max angle can measure just past it.
+    pub const VIABLE_ANGLE_EPSILON: f32 = 1e-4;
+
     /// Cast has a viable normal based on a max angle.
+    ///
+    /// The angle is between the normal and the up vector, a surface exactly at `max_angle` is viable
+    /// (within [`VIABLE_ANGLE_EPSILON`](Self::VIABLE_ANGLE_EPSILON)).
     pub fn viable(&self, up_vector: Vec3, max_angle: f32) -> bool {
-        self.normal.angle_between(up_vector).abs() < max_angle
+        self.normal.angle_between(up_vector).abs() <= max_angle + Self::VIABLE_ANGLE_EPSILON
+    }
+
+    /// Can the controller stand on this cast, `distance` below its float anchor along `up_vector`?
+    ///
+    /// The surface has to be [`viable`](Self::viable) and below the anchor, and the controller's
+    /// `offset` from its float distance has to be within `band`. Side contacts (e.g. the cast
+    /// grazing a wall) aren't ground, even within the float band.
+    pub fn supports(
+        &self,
+        up_vector: Vec3,
+        max_angle: f32,
+        distance: f32,
+        offset: f32,
+        band: RangeInclusive<f32>,
+    ) -> bool {
+        let walkable = self.viable(up_vector, max_angle);
+        let below = distance > 0.0;
+        walkable && below && band.contains(&offset)
     }
 }
 
@@ -524,6 +866,8 @@ pub struct GroundCastParams<'c, 'f> {
     pub max_toi: f32,
     /// Filter collider types/entities from this ground cast.
     pub filter: QueryFilter<'f>,
+    /// Whether to cast the shape or just a ray in [`GroundCastParams::cast`].
+    pub kind: GroundCastKind,
 }
 
 /// Arbitrary "slop"/"fudge" amount to adjust various things.
@@ -537,10 +881,11 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
         globals: &Query<&GlobalTransform>,
         up_vector: Vec3,
         iterations: usize,
-        gizmos: &mut Gizmos,
+        mut gizmos: Option<&mut Gizmos>,
     ) -> Option<(Entity, CastResult)> {
         for _ in 0..iterations {
-            if let Some((entity, cast)) = self.cast(ctx, globals, up_vector, gizmos) {
+            if let Some((entity, cast)) = self.cast(ctx, globals, up_vector, gizmos.as_deref_mut())
+            {
                 return Some((entity, cast));
             }
         }
@@ -557,11 +902,11 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
         max_angle: f32,
         up_vector: Vec3,
         iterations: usize,
-        gizmos: &mut Gizmos,
+        mut gizmos: Option<&mut Gizmos>,
     ) -> Option<(Entity, CastResult)> {
         for _ in 0..iterations {
             if let Some((entity, cast)) =
-                self.viable_cast(ctx, globals, up_vector, max_angle, gizmos)
+                self.viable_cast(ctx, globals, up_vector, max_angle, gizmos.as_deref_mut())
             {
                 return Some((entity, cast));
             }
@@ -576,21 +921,19 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
         ctx: &RapierContext,
         globals: &Query<&GlobalTransform>,
         up_vector: Vec3,
-        gizmos: &mut Gizmos,
+        mut gizmos: Option<&mut Gizmos>,
     ) -> Option<(Entity, CastResult)> {
-        self.correct_penetrations(ctx, globals);
-
-        let (entity, mut cast) = if let Some((entity, cast)) = self.cast_shape(ctx, gizmos) {
-            (entity, cast)
-        } else {
-            if let Some((entity, cast)) = self.cast_ray(ctx) {
-                (entity, cast)
-            } else {
-                return None;
+        let casted = match self.kind {
+            GroundCastKind::Shape => {
+                self.correct_penetrations(ctx, globals);
+                self.cast_shape(ctx, gizmos.as_deref_mut())
+                    .or_else(|| self.cast_ray(ctx))
             }
+            GroundCastKind::Ray => self.cast_ray(ctx),
         };
-        let Some(sampled_normal) = self.sample_normals(ctx, cast, up_vector, gizmos) else { return None };
-        cast.normal = sampled_normal;
+
+        let (entity, mut cast) = casted?;
+        cast.normal = self.sample_normals(ctx, cast, up_vector, gizmos)?;
 
         // Either none of the samples
         if cast.normal.length_squared() > 0.0 {
@@ -607,9 +950,9 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
         globals: &Query<&GlobalTransform>,
         up_vector: Vec3,
         max_angle: f32,
-        gizmos: &mut Gizmos,
+        mut gizmos: Option<&mut Gizmos>,
     ) -> Option<(Entity, CastResult)> {
-        let Some((entity, cast)) = self.cast(ctx, globals, up_vector, gizmos) else { return None };
+        let (entity, cast) = self.cast(ctx, globals, up_vector, gizmos.as_deref_mut())?;
 
         if cast.viable(up_vector, max_angle) {
             Some((entity, cast))
@@ -640,24 +983,32 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
     pub fn cast_shape(
         &self,
         ctx: &RapierContext,
-        gizmos: &mut Gizmos,
+        gizmos: Option<&mut Gizmos>,
     ) -> Option<(Entity, CastResult)> {
-        let Some((entity, toi)) = ctx
-            .cast_shape(self.position, self.rotation, self.direction, self.shape, self.max_toi, self.filter) else { return None };
+        let (entity, toi) = ctx.cast_shape(
+            self.position,
+            self.rotation,
+            self.direction,
+            self.shape,
+            self.max_toi,
+            self.filter,
+        )?;
 
-        if toi.status == TOIStatus::Penetrating || toi.toi <= std::f32::EPSILON {
+        if toi.status == TOIStatus::Penetrating || toi.toi <= f32::EPSILON {
             return None;
         }
 
         let (entity, cast) = (entity, CastResult::from_toi1(toi));
 
-        gizmos.ray(self.position, self.direction * cast.toi, Color::BLUE);
-        gizmos.sphere(
-            self.position + self.direction * cast.toi,
-            self.rotation,
-            0.3,
-            Color::BLUE,
-        );
+        if let Some(gizmos) = gizmos {
+            gizmos.ray(self.position, self.direction * cast.toi, Color::BLUE);
+            gizmos.sphere(
+                self.position + self.direction * cast.toi,
+                self.rotation,
+                0.3,
+                Color::BLUE,
+            );
+        }
 
         Some((entity, cast))
     }
@@ -685,7 +1036,7 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
     /// This is used so the controller doesn't repeatedly fall down
     /// a slope despite there being some viable ground right beneath the
     /// non-viable ground.
-    pub fn slide(&mut self, cast: CastResult, up_vector: Vec3, gizmos: &mut Gizmos) {
+    pub fn slide(&mut self, cast: CastResult, up_vector: Vec3, gizmos: Option<&mut Gizmos>) {
         let projected_position = self.position + self.direction * cast.toi;
         //let offset = cast.point.distance(projected_position);
 
@@ -693,7 +1044,9 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
         self.direction = down_tangent.normalize_or_zero();
         self.position = projected_position;
 
-        gizmos.ray(cast.point, down_tangent * 0.3, Color::CYAN);
+        if let Some(gizmos) = gizmos {
+            gizmos.ray(cast.point, down_tangent * 0.3, Color::CYAN);
+        }
         self.max_toi -= cast.toi;
         //max_toi -= (toi.toi - offset).max(0.0);
         self.max_toi = self.max_toi.max(0.0);
@@ -708,7 +1061,7 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
         ctx: &RapierContext,
         cast: CastResult,
         up_vector: Vec3,
-        gizmos: &mut Gizmos,
+        mut gizmos: Option<&mut Gizmos>,
     ) -> Option<Vec3> {
         // try to get a better normal rather than an edge interpolated normal.
         // project back onto original shape position
@@ -722,7 +1075,10 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
         // for the closest normal
         let mut sampled = Vec::new();
         let valid_radius = FUDGE * 2.0;
-        gizmos.sphere(cast.point, Quat::IDENTITY, valid_radius, Color::RED); // Bounding sphere of valid ray normals
+        if let Some(gizmos) = gizmos.as_deref_mut() {
+            // Bounding sphere of valid ray normals
+            gizmos.sphere(cast.point, Quat::IDENTITY, valid_radius, Color::RED);
+        }
         for sample in samples {
             let Some((_, inter)) = ctx.cast_ray_and_get_normal(
                 ray_origin - sample * FUDGE,
@@ -736,7 +1092,9 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
                 && inter.normal.length_squared() > 0.0
                 && inter.point.distance(cast.point) < valid_radius
             {
-                gizmos.ray(inter.point, inter.normal * 0.2, Color::RED);
+                if let Some(gizmos) = gizmos.as_deref_mut() {
+                    gizmos.ray(inter.point, inter.normal * 0.2, Color::RED);
+                }
                 sampled.push(inter.normal);
             }
         }
@@ -749,7 +1107,9 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
             weights += alignment;
         }
         let weighted_average = sum / weights;
-        gizmos.ray(cast.point, weighted_average * 0.5, Color::MAROON);
+        if let Some(gizmos) = gizmos {
+            gizmos.ray(cast.point, weighted_average * 0.5, Color::MAROON);
+        }
 
         if weighted_average.length_squared() > 0.0 {
             Some(weighted_average)
@@ -758,3 +1118,114 @@ impl<'c, 'f> GroundCastParams<'c, 'f> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cast(normal: Vec3, point: Vec3) -> CastResult {
+        CastResult {
+            toi: 0.0,
+            normal,
+            point,
+        }
+    }
+
+    #[test]
+    fn supported_by_walkable_ground_within_band() {
+        let max_angle = 45f32.to_radians();
+        let floor = cast(Vec3::Y, Vec3::new(0.0, -1.0, 0.0));
+        assert!(floor.supports(Vec3::Y, max_angle, 1.0, 0.0, -0.1..=0.1));
+        assert!(floor.supports(Vec3::Y, max_angle, 1.05, -0.05, -0.1..=0.1));
+
+        // Too far from the float distance either way.
+        assert!(!floor.supports(Vec3::Y, max_angle, 1.5, -0.5, -0.1..=0.1));
+        assert!(!floor.supports(Vec3::Y, max_angle, 0.5, 0.5, -0.1..=0.1));
+
+        // Too steep.
+        let slope = cast(Vec3::new(1.0, 0.5, 0.0).normalize(), Vec3::NEG_Y);
+        assert!(!slope.supports(Vec3::Y, max_angle, 1.0, 0.0, -0.1..=0.1));
+    }
+
+    #[test]
+    fn viable_at_exactly_max_angle() {
+        for degrees in [20.0f32, 45.0, 50.0, 60.0, 89.0] {
+            let max_angle = degrees.to_radians();
+            for up in [Vec3::Y, Vec3::new(1.0, 2.0, -0.5).normalize()] {
+                let axis = up.any_orthonormal_vector();
+                let at = |angle: f32| cast(Quat::from_axis_angle(axis, angle) * up, Vec3::ZERO);
+
+                assert!(at(max_angle).viable(up, max_angle), "{degrees}° at {up}");
+                assert!(at(max_angle - 1e-3).viable(up, max_angle));
+                assert!(!at(max_angle + 1e-3).viable(up, max_angle));
+            }
+        }
+    }
+
+    #[test]
+    fn not_supported_mid_air_next_to_tall_wall() {
+        let max_angle = 45f32.to_radians();
+
+        // The cast grazes a wall beside the controller, with a horizontal normal level with
+        // or above the anchor, and then further down within the float band.
+        let level = cast(Vec3::NEG_X, Vec3::new(0.5, 0.0, 0.0));
+        assert!(!level.supports(Vec3::Y, max_angle, 0.0, 1.0, -0.1..=1.5));
+        let above = cast(Vec3::NEG_X, Vec3::new(0.5, 0.3, 0.0));
+        assert!(!above.supports(Vec3::Y, max_angle, -0.3, 1.3, -0.1..=1.5));
+        let below = cast(Vec3::NEG_X, Vec3::new(0.5, -1.0, 0.0));
+        assert!(!below.supports(Vec3::Y, max_angle, 1.0, 0.0, -0.1..=0.1));
+
+        // Even a walkable normal above the anchor isn't ground.
+        let ledge = cast(Vec3::Y, Vec3::new(0.5, 0.3, 0.0));
+        assert!(!ledge.supports(Vec3::Y, max_angle, -0.3, 1.3, -0.1..=1.5));
+    }
+
+    #[test]
+    fn smooth_normal_disabled_snaps() {
+        let slope = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let caster = GroundCaster::default();
+        assert_eq!(caster.smooth_normal(Vec3::Y, slope, 1.0 / 60.0), slope);
+
+        // Nothing to smooth from yet.
+        let caster = GroundCaster {
+            normal_smoothing: 10.0,
+            ..default()
+        };
+        assert_eq!(caster.smooth_normal(Vec3::ZERO, slope, 1.0 / 60.0), slope);
+    }
+
+    #[test]
+    fn smooth_normal_eases_towards_normal() {
+        let slope = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let caster = GroundCaster {
+            normal_smoothing: 10.0,
+            ..default()
+        };
+
+        let mut normal = Vec3::Y;
+        let mut last_distance = normal.distance(slope);
+        for _ in 0..60 {
+            normal = caster.smooth_normal(normal, slope, 1.0 / 60.0);
+            assert!((normal.length() - 1.0).abs() < 1e-5);
+
+            let distance = normal.distance(slope);
+            assert!(distance < last_distance);
+            last_distance = distance;
+        }
+
+        assert!(last_distance < 1e-3);
+    }
+
+    #[test]
+    fn smooth_normal_is_framerate_independent() {
+        let slope = Vec3::new(0.0, 1.0, 1.0).normalize();
+        let caster = GroundCaster {
+            normal_smoothing: 5.0,
+            ..default()
+        };
+
+        let once = caster.smooth_normal(Vec3::Y, slope, 0.1);
+        let twice = caster.smooth_normal(caster.smooth_normal(Vec3::Y, slope, 0.05), slope, 0.05);
+        assert!(once.angle_between(twice) < 0.02);
+    }
+}
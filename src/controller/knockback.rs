@@ -0,0 +1,75 @@
+use crate::controller::*;
+
+/// Knocking the controller back with an impulse, taking away movement control for a while so
+/// the movement forces don't immediately cancel it out.
+///
+/// Control is blended back in linearly over the lockout.
+///
+/// This isn't part of [`Controller`], add a [`KnockbackBundle`] to controllers that can be knocked back.
+#[derive(Component, Debug, Default, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct Knockback {
+    /// Impulse to apply next frame, see [`Knockback::apply`].
+    pub impulse: Vec3,
+    /// How long the current movement control lockout lasts.
+    pub control_lockout: f32,
+    /// Timer for tracking `control_lockout`, this is the remaining time of the lockout.
+    pub lockout_timer: f32,
+}
+
+impl Knockback {
+    /// Knock the controller back with `impulse`, taking away movement control for `control_lockout` seconds.
+    ///
+    /// Multiple knockbacks in the same frame are added together, and the longest lockout is kept.
+    pub fn apply(&mut self, impulse: Vec3, control_lockout: f32) {
+        self.impulse += impulse;
+        if control_lockout >= self.lockout_timer {
+            self.control_lockout = control_lockout;
+            self.lockout_timer = control_lockout;
+        }
+    }
+
+    /// How much movement control the controller has, from `0.0` right after a knockback to `1.0` with full control.
+    pub fn control(&self) -> f32 {
+        if self.control_lockout > 0.0 {
+            1.0 - (self.lockout_timer / self.control_lockout).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Calculated force for knockback.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct KnockbackForce {
+    /// Linear force from the knockback impulse.
+    pub linear: Vec3,
+}
+
+/// Components needed for a controller to be knocked back.
+#[derive(Bundle, Default)]
+pub struct KnockbackBundle {
+    /// Pending knockback and control lockout.
+    pub knockback: Knockback,
+    /// Calculated force for knockback.
+    pub knockback_force: KnockbackForce,
+}
+
+/// Apply pending knockback impulses and scale down movement while control is locked out.
+pub fn knockback_force(
+    mut query: Query<(&mut KnockbackForce, &mut Knockback, &mut MovementForce), Simulated>,
+    ctx: Res<RapierContext>,
+) {
+    let dt = ctx.integration_parameters.dt;
+    for (mut force, mut knockback, mut movement_force) in &mut query {
+        knockback.lockout_timer = (knockback.lockout_timer - dt).max(0.0);
+
+        force.linear = knockback.impulse / dt;
+        knockback.impulse = Vec3::ZERO;
+
+        let control = knockback.control();
+        movement_force.linear *= control;
+        movement_force.angular *= control;
+    }
+}
@@ -0,0 +1,209 @@
+use crate::controller::*;
+
+/// Detecting ledges in front of the controller while airborne, and hanging from them.
+///
+/// A ledge is the top edge of a wall in front of the controller, between `min_height` and
+/// `max_height` above the controller's origin with nothing blocking the space above it.
+/// Climbing up (mantling) is left to the game, e.g. by moving the controller on top of
+/// [`Ledge::position`] and clearing [`LedgeGrab::hanging`].
+///
+/// This isn't part of [`Controller`], add it to controllers that should be able to grab ledges.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct LedgeGrab {
+    /// How far in front of the controller's origin to look for a ledge.
+    pub reach_forward: f32,
+    /// Lowest height, above the controller's origin, a ledge can be grabbed at.
+    pub min_height: f32,
+    /// Highest height, above the controller's origin, a ledge can be grabbed at.
+    pub max_height: f32,
+    /// Hang from detected ledges, holding the controller in place.
+    ///
+    /// If false, ledges are only detected and a [`LedgeDetected`] event sent.
+    pub hang: bool,
+    /// Ledge currently being hung from.
+    ///
+    /// Set this to `None` to let go, crouching (see [`ControllerInput::crouching`]) also lets go.
+    #[reflect(ignore)]
+    pub hanging: Option<Ledge>,
+    /// Ledge detected this frame, if any.
+    #[reflect(ignore)]
+    pub detected: Option<Ledge>,
+}
+
+impl Default for LedgeGrab {
+    fn default() -> Self {
+        Self {
+            reach_forward: 0.8,
+            min_height: 0.5,
+            max_height: 1.2,
+            hang: true,
+            hanging: None,
+            detected: None,
+        }
+    }
+}
+
+impl LedgeGrab {
+    /// Are we currently hanging from a ledge?
+    pub fn hanging(&self) -> bool {
+        self.hanging.is_some()
+    }
+}
+
+/// A grabbable ledge.
+#[derive(Debug, Copy, Clone)]
+pub struct Ledge {
+    /// Entity the ledge belongs to.
+    pub entity: Entity,
+    /// Point on top of the ledge, at its edge.
+    pub position: Vec3,
+    /// Normal of the wall below the ledge, pointing towards the controller.
+    pub normal: Vec3,
+}
+
+/// Sent when a controller finds a ledge it can grab.
+#[derive(Event, Debug, Clone)]
+pub struct LedgeDetected {
+    /// The controller that found the ledge.
+    pub entity: Entity,
+    /// Entity the ledge belongs to.
+    pub ledge: Entity,
+    /// Point on top of the ledge, at its edge.
+    pub position: Vec3,
+    /// Normal of the wall below the ledge, pointing towards the controller.
+    pub normal: Vec3,
+}
+
+/// Controllers that can grab ledges, and the forces overridden while hanging.
+type LedgeGrabQuery = (
+    Entity,
+    &'static mut LedgeGrab,
+    &'static mut MovementForce,
+    &'static mut GravityForce,
+    &'static mut FloatForce,
+    &'static GlobalTransform,
+    &'static Gravity,
+    &'static GroundCaster,
+    &'static ControllerInput,
+    &'static Grounded,
+    &'static ControllerVelocity,
+    &'static ControllerMass,
+);
+
+/// Find ledges in front of airborne controllers, and hold them in place while hanging.
+pub fn ledge_grab(
+    mut query: Query<LedgeGrabQuery, Simulated>,
+    ctx: Res<RapierContext>,
+    mut events: EventWriter<LedgeDetected>,
+) {
+    let dt = ctx.integration_parameters.dt;
+    for (
+        entity,
+        mut ledge_grab,
+        mut movement_force,
+        mut gravity_force,
+        mut float_force,
+        tf,
+        gravity,
+        caster,
+        input,
+        grounded,
+        velocity,
+        mass,
+    ) in &mut query
+    {
+        let previous = ledge_grab.detected.take();
+
+        if **grounded || input.crouching {
+            ledge_grab.hanging = None;
+        }
+
+        if !**grounded && !ledge_grab.hanging() && !input.crouching {
+            let predicate =
+                |collider| collider != entity && !caster.exclude_from_ground.contains(&collider);
+            let filter = QueryFilter::new().exclude_sensors().predicate(&predicate);
+            let direction = gravity.project(input.movement).normalize_or_zero();
+
+            if direction != Vec3::ZERO {
+                ledge_grab.detected = find_ledge(
+                    &ctx,
+                    filter,
+                    &ledge_grab,
+                    tf.translation(),
+                    direction,
+                    gravity.up_vector,
+                    caster.max_ground_angle,
+                );
+            }
+
+            if let Some(ledge) = ledge_grab.detected {
+                if previous.is_none() {
+                    events.send(LedgeDetected {
+                        entity,
+                        ledge: ledge.entity,
+                        position: ledge.position,
+                        normal: ledge.normal,
+                    });
+                }
+
+                if ledge_grab.hang {
+                    ledge_grab.hanging = Some(ledge);
+                }
+            }
+        }
+
+        if ledge_grab.hanging() {
+            // Cancel out our velocity and anything else pulling us around.
+            movement_force.linear = -velocity.linear * mass.mass / dt;
+            gravity_force.linear = Vec3::ZERO;
+            float_force.linear = Vec3::ZERO;
+        }
+    }
+}
+
+fn find_ledge(
+    ctx: &RapierContext,
+    filter: QueryFilter,
+    ledge_grab: &LedgeGrab,
+    position: Vec3,
+    direction: Vec3,
+    up_vector: Vec3,
+    max_ground_angle: f32,
+) -> Option<Ledge> {
+    let reach = ledge_grab.reach_forward;
+
+    // There needs to be a wall in front of us within the reach band.
+    let low = position + up_vector * ledge_grab.min_height;
+    let (_, wall) = ctx
+        .cast_ray_and_get_normal(low, direction, reach, true, filter)
+        .map(|(entity, inter)| (entity, CastResult::from(inter)))?;
+    if wall.viable(up_vector, max_ground_angle) {
+        return None;
+    }
+
+    // The space above the ledge has to be open.
+    let high = position + up_vector * ledge_grab.max_height;
+    if ctx
+        .cast_ray(high, direction, wall.toi + FUDGE, true, filter)
+        .is_some()
+    {
+        return None;
+    }
+
+    // Find the top of the ledge.
+    let above = high + direction * (wall.toi + FUDGE);
+    let height = ledge_grab.max_height - ledge_grab.min_height;
+    let (entity, top) = ctx
+        .cast_ray_and_get_normal(above, -up_vector, height, true, filter)
+        .map(|(entity, inter)| (entity, CastResult::from(inter)))?;
+    if !top.viable(up_vector, max_ground_angle) {
+        return None;
+    }
+
+    Some(Ledge {
+        entity: ctx.collider_parent(entity).unwrap_or(entity),
+        position: top.point - direction * FUDGE,
+        normal: wall.normal,
+    })
+}
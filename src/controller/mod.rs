@@ -1,16 +1,34 @@
-use bevy::prelude::*;
+use bevy::{ecs::query::WorldQuery, prelude::*};
 use bevy_rapier3d::prelude::*;
 
+mod backend;
+mod camera;
+mod ceiling;
+mod crouch;
+mod dash;
 mod gravity;
 mod ground;
-mod input;
+mod knockback;
+mod ledge;
 mod movement;
 mod orientation;
+mod slide;
+mod snapshot;
+mod state;
+mod step;
+mod stuck;
+mod surface;
+mod swim;
+mod wall;
 
 use crate::physics::*;
-use crate::Spring;
+use crate::{grounded::*, input::*, jump::*, Spring};
 
-pub use {gravity::*, ground::*, input::*, movement::*, orientation::*};
+pub use {
+    backend::*, camera::*, ceiling::*, crouch::*, dash::*, gravity::*, ground::*, knockback::*,
+    ledge::*, movement::*, orientation::*, slide::*, snapshot::*, state::*, step::*, stuck::*,
+    surface::*, swim::*, wall::*,
+};
 
 /// Components required for calculating controller forces.
 #[derive(Bundle)]
@@ -30,6 +48,8 @@ pub struct Controller {
     pub viable_ground_cast: ViableGroundCast,
     /// Is the controller currently considered on viable ground.
     pub grounded: Grounded,
+    /// Where the controller is touching the ground.
+    pub ground_contact: GroundContact,
     /// Force applied to the ground the controller is on.
     pub ground_force: GroundForce,
 
@@ -54,6 +74,9 @@ pub struct Controller {
     /// Calculated force for keeping the controller upright.
     pub upright_force: UprightForce,
 
+    /// What the controller is currently doing.
+    pub state: ControllerState,
+
     /// How should the forces be applied to the physics engine.
     pub force_settings: ForceSettings,
 }
@@ -68,6 +91,7 @@ impl Default for Controller {
             ground_cast: default(),
             viable_ground_cast: default(),
             grounded: default(),
+            ground_contact: default(),
             ground_force: default(),
 
             movement: default(),
@@ -80,6 +104,8 @@ impl Default for Controller {
             upright: default(),
             upright_force: default(),
 
+            state: default(),
+
             force_settings: default(),
         }
     }
@@ -105,6 +131,90 @@ impl Default for ForceSettings {
     }
 }
 
+/// Stops the controller from applying any forces while present.
+///
+/// Forces are zeroed rather than left over, so removing this starts the controller from rest.
+/// Jumping, dashing, knockback, ledge grabs and ceiling hits are paused too, so jump presses
+/// and pending [`Knockback`] impulses are kept until the controller is unfrozen.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct Freeze {
+    /// Also zero the controller's velocity every frame while frozen.
+    pub zero_velocity: bool,
+}
+
+/// Fly around freely while present, e.g. for debugging or spectating.
+///
+/// [`ControllerInput::movement`], including its vertical part, directly drives the velocity
+/// and every other controller force (gravity, float, jumping, etc.) is turned off.
+/// Jumping, dashing, ledge grabs, ceiling hits and ground detection are skipped entirely, so no
+/// [`JumpEvent`], [`LandedEvent`], etc. are sent while flying through things.
+/// Removing this goes back to the normal controller behavior.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct NoClip {
+    /// How fast to fly at full input.
+    pub speed: f32,
+    /// Turn the controller's collider into a [`Sensor`] while flying, so it can pass through things.
+    pub disable_collisions: bool,
+}
+
+impl Default for NoClip {
+    fn default() -> Self {
+        Self {
+            speed: 10.0,
+            disable_collisions: true,
+        }
+    }
+}
+
+/// Controllers that are neither [`Freeze`]-ed nor in [`NoClip`], for systems that shouldn't
+/// use up presses or send events while the controller isn't simulated.
+pub(crate) type Simulated = (Without<Freeze>, Without<NoClip>);
+
+/// Convenience query for reading the forces calculated for a controller, e.g. for telemetry or animation.
+///
+/// These are calculated every frame in [`WanderlustSet::Compute`](crate::WanderlustSet::Compute),
+/// so read them after that. `total` is everything added together and applied to the controller.
+///
+/// ```ignore
+/// fn log_forces(controllers: Query<(Entity, ControllerForces)>) {
+///     for (entity, forces) in &controllers {
+///         info!(
+///             "{entity:?}: total {:.2?}, gravity {:.2?}, float {:.2?}, jump {:.2?}, movement {:.2?}",
+///             forces.total.linear,
+///             forces.gravity.linear,
+///             forces.float.linear,
+///             forces.jump.linear,
+///             forces.movement.linear,
+///         );
+///     }
+/// }
+///
+/// app.add_systems(Update, log_forces.after(WanderlustSet::Compute));
+/// ```
+#[derive(WorldQuery)]
+pub struct ControllerForces {
+    /// All of the forces added together, see [`accumulate_forces`].
+    pub total: &'static ControllerForce,
+    /// Force applied to the ground the controller is standing on.
+    pub ground: &'static GroundForce,
+    /// Force from [`Gravity`].
+    pub gravity: &'static GravityForce,
+    /// Force from [`Float`].
+    pub float: &'static FloatForce,
+    /// Force from [`Jump`].
+    pub jump: &'static JumpForce,
+    /// Force from [`Movement`].
+    pub movement: &'static MovementForce,
+    /// Force from [`Upright`].
+    pub upright: &'static UprightForce,
+    /// Force from [`Dash`], if the controller can dash.
+    pub dash: Option<&'static DashForce>,
+    /// Force from [`Knockback`], if the controller can be knocked back.
+    pub knockback: Option<&'static KnockbackForce>,
+}
+
 /// Add all forces together into a single force to be applied to the physics engine.
 pub fn accumulate_forces(
     globals: Query<&GlobalTransform>,
@@ -113,67 +223,173 @@ pub fn accumulate_forces(
         &ForceSettings,
         &mut ControllerForce,
         &mut GroundForce,
-        &FloatForce,
-        &UprightForce,
-        &MovementForce,
-        &JumpForce,
-        &GravityForce,
+        &mut FloatForce,
+        &mut UprightForce,
+        &mut MovementForce,
+        &mut JumpForce,
+        &mut GravityForce,
+        Option<&mut DashForce>,
+        Option<&mut KnockbackForce>,
         &ViableGroundCast,
+        (Option<&Freeze>, Option<&NoClip>, &ControllerInput),
+        &Gravity,
+        &ControllerVelocity,
+        &ControllerMass,
     )>,
+    ctx: Res<RapierContext>,
 ) {
-    for (
-        settings,
-        mut force,
-        mut ground_force,
-        float,
-        upright,
-        movement,
-        jump,
-        gravity,
-        viable_ground,
-    ) in &mut forces
-    {
-        /*
-        info!(
-            "movement: {:.2?}, jump: {:.2?}, float: {:.2?}, gravity: {:.2?}",
-            movement.linear, jump.linear, float.linear, gravity.linear
-        );
-        */
-        force.linear = movement.linear + jump.linear + float.linear + gravity.linear;
-        force.angular = movement.angular + upright.angular;
-        //force.angular = movement.angular;
-
-        let opposing_force = -(movement.linear * settings.opposing_movement_force_scale
-            + (jump.linear + float.linear) * settings.opposing_force_scale);
-
-        if let Some(ground) = viable_ground.current() {
-            let ground_global = match globals.get(ground.entity) {
-                Ok(global) => global,
-                _ => &GlobalTransform::IDENTITY,
-            };
-
-            let ground_mass = if let Ok(mass) = masses.get(ground.entity) {
-                mass.0.clone()
-            } else {
-                MassProperties::default()
-            };
+    let dt = ctx.integration_parameters.dt;
+    forces.par_iter_mut().for_each_mut(
+        |(
+            settings,
+            mut force,
+            mut ground_force,
+            mut float,
+            mut upright,
+            mut movement,
+            mut jump,
+            mut gravity,
+            mut dash,
+            mut knockback,
+            viable_ground,
+            (freeze, no_clip, input),
+            gravity_settings,
+            velocity,
+            mass,
+        )| {
+            if freeze.is_some() || no_clip.is_some() {
+                *force = default();
+                *ground_force = default();
+                *float = default();
+                *upright = default();
+                *movement = default();
+                *jump = default();
+                *gravity = default();
+                if let Some(dash) = dash.as_deref_mut() {
+                    *dash = default();
+                }
+                if let Some(knockback) = knockback.as_deref_mut() {
+                    *knockback = default();
+                }
 
-            let com = ground_global.transform_point(ground_mass.local_center_of_mass);
-            ground_force.linear = opposing_force;
-            ground_force.angular = (ground.cast.point - com).cross(opposing_force);
+                if let (Some(no_clip), None) = (no_clip, freeze) {
+                    let goal = input.movement.clamp_length_max(1.0) * no_clip.speed;
+                    force.linear = (goal - velocity.linear) * mass.mass / dt;
+                    force.angular = -velocity.angular * mass.inertia / dt;
+                }
+                return;
+            }
 
-            #[cfg(feature = "debug_lines")]
-            {
-                let color = if opposing_force.dot(gravity_settings.up_vector) < 0.0 {
-                    Color::RED
+            /*
+            info!(
+                "movement: {:.2?}, jump: {:.2?}, float: {:.2?}, gravity: {:.2?}",
+                movement.linear, jump.linear, float.linear, gravity.linear
+            );
+            */
+            let dash = dash.map_or(Vec3::ZERO, |dash| dash.linear);
+            // Knockback comes from outside the controller, so it isn't pushed onto the ground.
+            let knockback = knockback.map_or(Vec3::ZERO, |knockback| knockback.linear);
+            force.linear =
+                movement.linear + jump.linear + float.linear + gravity.linear + dash + knockback;
+            force.angular = movement.angular + upright.angular;
+            //force.angular = movement.angular;
+
+            // Clamp the fall speed last, so no other force can push past it.
+            let fall_clamp =
+                gravity_settings.max_fall_speed_force(velocity.linear, force.linear, mass.mass, dt);
+            force.linear += fall_clamp;
+
+            let opposing_force = -((movement.linear + dash)
+                * settings.opposing_movement_force_scale
+                + (jump.linear + float.linear) * settings.opposing_force_scale);
+
+            if let Some(ground) = viable_ground.current() {
+                let ground_global = match globals.get(ground.entity) {
+                    Ok(global) => global,
+                    _ => &GlobalTransform::IDENTITY,
+                };
+
+                let ground_mass = if let Ok(mass) = masses.get(ground.entity) {
+                    mass.0.clone()
                 } else {
-                    Color::BLUE
+                    MassProperties::default()
                 };
-                //gizmos.line(ground.cast.point, ground.cast.point + opposing_force, color);
+
+                let com = ground_global.transform_point(ground_mass.local_center_of_mass);
+                ground_force.linear = opposing_force;
+                ground_force.angular = (ground.cast.point - com).cross(opposing_force);
+            } else {
+                ground_force.linear = opposing_force;
+                ground_force.angular = Vec3::ZERO;
             }
-        } else {
-            ground_force.linear = opposing_force;
-            ground_force.angular = Vec3::ZERO;
-        }
+        },
+    );
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::{SpringStrength, Strength};
+    use serde::{de::DeserializeOwned, Serialize};
+
+    /// Serialize to RON and back, checking nothing changed by serializing the result again.
+    fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+        let ron = ron::to_string(value).unwrap();
+        let deserialized: T = ron::from_str(&ron).unwrap();
+        assert_eq!(ron::to_string(&deserialized).unwrap(), ron);
+        deserialized
+    }
+
+    #[test]
+    fn default_controller_round_trip() {
+        let controller = Controller::default();
+        round_trip(&controller.gravity);
+        round_trip(&controller.ground_caster);
+        round_trip(&controller.movement);
+        round_trip(&controller.jump);
+        round_trip(&controller.float);
+        round_trip(&controller.upright);
+        round_trip(&controller.float.spring);
+    }
+
+    #[test]
+    fn tuned_controller_round_trip() {
+        let movement = Movement {
+            max_speed: 12.0,
+            acceleration: Strength::Curve(vec![Vec2::new(0.0, 20.0), Vec2::new(1.0, 5.0)]),
+            ..default()
+        };
+        let movement = round_trip(&movement);
+        assert_eq!(movement.max_speed, 12.0);
+        assert!(matches!(movement.acceleration, Strength::Curve(ref points) if points.len() == 2));
+
+        let spring = Spring {
+            strength: SpringStrength::AngularFrequencyPerAxis(Vec3::new(10.0, 20.0, 10.0)),
+            damping: 0.7,
+        };
+        let spring = round_trip(&spring);
+        assert_eq!(spring.damping, 0.7);
+    }
+
+    #[test]
+    fn runtime_state_is_not_serialized() {
+        let jump = Jump {
+            jumps: 3,
+            remaining_jumps: 0,
+            buffer_timer: 0.2,
+            coyote_timer: 0.1,
+            ..default()
+        };
+        let jump = round_trip(&jump);
+        assert_eq!(jump.jumps, 3);
+        assert_eq!(jump.remaining_jumps, Jump::default().remaining_jumps);
+        assert!(!jump.buffered());
+        assert!(!jump.in_coyote_time());
+
+        let float = Float {
+            current_distance: Some(0.5),
+            ..default()
+        };
+        assert_eq!(round_trip(&float).current_distance, None);
     }
 }
@@ -5,22 +5,128 @@ use crate::{controller::*, spring::Strength};
 /// This includes directional movement and jumping.
 #[derive(Component, Debug, Clone, Reflect)]
 #[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Movement {
     /// How fast the controller will get to the `max_speed`.
     pub acceleration: Strength,
+    /// Multiplier for `acceleration` while the controller isn't grounded.
+    ///
+    /// Lower values give floatier air control, `1.0` controls the same as on the ground.
+    pub air_control: f32,
+    /// Use Quake-style air acceleration while airborne instead of the usual movement.
+    ///
+    /// This only adds speed along the input direction up to `air_speed_cap`, so speed can be
+    /// gained by turning while strafing in the air (i.e. bunny-hopping/strafe-jumping).
+    pub air_strafe: bool,
+    /// Maximum speed that can be added along the input direction while air strafing.
+    pub air_speed_cap: f32,
+    /// How quickly speed is added while air strafing, scaled by the target speed.
+    pub air_strafe_acceleration: f32,
     /// How fast our controller will move.
+    ///
+    /// This only applies to the horizontal (perpendicular to the up vector) speed. The controller won't
+    /// accelerate itself past this, but if something else pushes it faster it can still steer.
     pub max_speed: f32,
+    /// How fast our controller will move while [`ControllerInput::sprinting`].
+    pub sprint_speed: f32,
+    /// How quickly, in units per second, the target speed moves between
+    /// `max_speed` and `sprint_speed` when sprinting starts/stops.
+    pub sprint_acceleration: f32,
+    /// Speed the controller is currently trying to reach, this moves between
+    /// `max_speed` and `sprint_speed` depending on [`ControllerInput::sprinting`].
+    ///
+    /// `None` while walking at `max_speed`, so changes to `max_speed` apply straight away.
+    /// Use [`Movement::target_speed()`] to read the current speed either way.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub target_speed: Option<f32>,
     /// Scales movement force. This is useful to ensure movement does not
     /// affect vertical velocity (by setting it to e.g. `Vec3(1.0, 0.0, 1.0)`).
     pub force_scale: ForceScale,
+    /// Project movement onto the ground we are standing on, so the controller moves
+    /// along slopes at the same speed as on flat ground.
+    ///
+    /// While this is active `force_scale` is ignored, since the movement is already along the ground.
+    pub project_on_ground: bool,
+    /// Extra braking deceleration (units per second squared) applied while grounded with no movement input.
+    ///
+    /// This is on top of the friction between the controller and the ground, and is
+    /// relative to the ground's velocity so it won't fight moving platforms.
+    pub deceleration: f32,
+    /// Instantly match changes in the velocity of the ground we are standing on,
+    /// so the controller rides along with moving/rotating platforms.
+    ///
+    /// This is skipped for dynamic rigid bodies, since our own forces on them would feed back into us.
+    pub carry_with_ground: bool,
+    /// Ground entity and its velocity at the contact point last frame, used for `carry_with_ground`.
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub last_carry_velocity: Option<(Entity, Vec3)>,
+    /// How much to resist sliding down ground too steep to stand on (see [`GroundCaster::max_ground_angle`]).
+    ///
+    /// `0.0` slides with the full force of gravity along the slope, `1.0` cancels out gravity along the slope.
+    pub slide_friction: f32,
+    /// Speed multiplier when moving away from [`Upright::forward_vector`].
+    ///
+    /// Diagonal movement interpolates between this and the forward/strafe speed.
+    /// Has no effect if the controller has no forward vector.
+    pub backward_speed_scale: f32,
+    /// Speed multiplier when moving perpendicular to [`Upright::forward_vector`].
+    pub strafe_speed_scale: f32,
+    /// Radial deadzone applied to [`ControllerInput::movement`].
+    ///
+    /// Input with a length below this is ignored, and the remaining range is
+    /// rescaled to `0.0..=1.0` so a fully deflected stick still reaches the target speed.
+    pub input_deadzone: f32,
+    /// Speed multiplier when moving straight up a slope as steep as [`GroundCaster::max_ground_angle`].
+    ///
+    /// Shallower slopes interpolate towards `1.0`, so `0.0` means the controller
+    /// slows to a stop as the slope approaches the max ground angle.
+    pub uphill_speed_scale: f32,
+    /// Speed multiplier when moving straight down a slope as steep as [`GroundCaster::max_ground_angle`].
+    pub downhill_speed_scale: f32,
+    /// Horizontal speed, relative to the ground, above which the controller is considered moving.
+    ///
+    /// See [`MovementStateEvent`].
+    pub moving_speed_threshold: f32,
+    /// Horizontal speed, relative to the ground, below which the controller is considered stopped.
+    ///
+    /// This should be lower than `moving_speed_threshold` to avoid flickering between states.
+    pub stopped_speed_threshold: f32,
+    /// Is the controller currently considered moving.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub moving: bool,
     /// Scales movement force when we are slipping.
     /// If this is not `Vec3(1.0, 1.0, 1.0)` then the character can try to
     /// move up the slope.
     pub slip_force_scale: Vec3,
+    /// Don't push into walls found by a [`WallCaster`], so running face first
+    /// into a corner doesn't jitter against the physics engine's contacts.
+    pub clip_to_walls: bool,
+    /// Redirect movement into walls found by a [`WallCaster`] along them, rather than stopping dead.
+    ///
+    /// The speed along the wall shrinks the more head-on the controller moves into it, see
+    /// `wall_slide_speed_retention`.
+    pub wall_slide: bool,
+    /// How much of the original speed is kept while sliding along a wall.
+    ///
+    /// `0.0` only keeps the speed along the wall, `1.0` keeps the full speed no matter the angle.
+    pub wall_slide_speed_retention: f32,
+    /// Max force movement can apply in a single frame, regardless of how far off the velocity is.
+    ///
+    /// This trades responsiveness for stability, e.g. so a sudden large change in input can't
+    /// launch the controller through thin colliders. It caps the combined movement, friction and
+    /// braking force after the `max_speed` limit, so it can only make reaching the target speed slower.
+    /// `None` doesn't cap the force.
+    pub max_acceleration_force: Option<f32>,
 }
 
 /// Determine force scale for movement.
 #[derive(Debug, Default, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ForceScale {
     /// Use the inverse of `Gravity::up_vector` for a force scale.
     #[default]
@@ -35,14 +141,201 @@ impl Default for Movement {
     fn default() -> Self {
         Self {
             acceleration: Strength::Scaled(10.0),
+            air_control: 1.0,
+            air_strafe: false,
+            air_speed_cap: 0.75,
+            air_strafe_acceleration: 10.0,
             max_speed: 5.0,
+            sprint_speed: 10.0,
+            sprint_acceleration: 20.0,
+            target_speed: None,
             force_scale: default(),
+            project_on_ground: false,
+            deceleration: 0.0,
+            carry_with_ground: true,
+            last_carry_velocity: None,
+            slide_friction: 0.0,
+            backward_speed_scale: 1.0,
+            strafe_speed_scale: 1.0,
+            input_deadzone: 0.0,
+            uphill_speed_scale: 1.0,
+            downhill_speed_scale: 1.0,
+            moving_speed_threshold: 0.5,
+            stopped_speed_threshold: 0.2,
+            moving: false,
             slip_force_scale: Vec3::splat(1.0),
+            clip_to_walls: true,
+            wall_slide: true,
+            wall_slide_speed_retention: 0.0,
+            max_acceleration_force: None,
         }
     }
 }
 
 impl Movement {
+    /// Speed the controller is currently trying to reach, the `target_speed` field or `max_speed`
+    /// if it isn't set.
+    pub fn target_speed(&self) -> f32 {
+        self.target_speed.unwrap_or(self.max_speed)
+    }
+
+    /// Move the target speed towards the walking/sprinting speed.
+    pub fn update_target_speed(&mut self, sprinting: bool, dt: f32) {
+        let desired = if sprinting {
+            self.sprint_speed
+        } else {
+            self.max_speed
+        };
+
+        let max_change = self.sprint_acceleration * dt;
+        let change = (desired - self.target_speed()).clamp(-max_change, max_change);
+        let target_speed = self.target_speed() + change;
+        self.target_speed = (sprinting || target_speed != self.max_speed).then_some(target_speed);
+    }
+
+    /// Quake-style air acceleration force, see [`air_strafe`](Self::air_strafe).
+    pub fn air_strafe_force(&self, velocity: Vec3, goal_vel: Vec3, mass: f32, dt: f32) -> Vec3 {
+        let wish_speed = goal_vel.length();
+        let wish_dir = goal_vel.normalize_or_zero();
+
+        let add_speed = wish_speed.min(self.air_speed_cap) - velocity.dot(wish_dir);
+        if add_speed <= 0.0 {
+            return Vec3::ZERO;
+        }
+
+        let accel_speed = (self.air_strafe_acceleration * wish_speed * dt).min(add_speed);
+        wish_dir * accel_speed * mass / dt
+    }
+
+    /// Apply [`Movement::input_deadzone`] to the movement input, keeping its magnitude.
+    pub fn apply_deadzone(&self, input: Vec3) -> Vec3 {
+        let input = input.clamp_length_max(1.0);
+        let length = input.length();
+        if length <= self.input_deadzone || length == 0.0 {
+            return Vec3::ZERO;
+        }
+
+        let deadzone = self.input_deadzone.clamp(0.0, 1.0);
+        let magnitude = (length - deadzone) / (1.0 - deadzone);
+        input / length * magnitude
+    }
+
+    /// Braking force bringing `relative_velocity` to a stop at [`Movement::deceleration`] without overshooting.
+    pub fn deceleration_force(&self, relative_velocity: Vec3, mass: f32, dt: f32) -> Vec3 {
+        let speed = relative_velocity.length();
+        if speed == 0.0 || dt == 0.0 {
+            return Vec3::ZERO;
+        }
+
+        let decel = self.deceleration.max(0.0).min(speed / dt);
+        -relative_velocity / speed * decel * mass
+    }
+
+    /// Speed multiplier for moving in `goal_dir` relative to the `forward` facing direction.
+    pub fn directional_speed_scale(&self, up_vector: Vec3, forward: Vec3, goal_dir: Vec3) -> f32 {
+        let right = up_vector.cross(forward).normalize_or_zero();
+        let forward = right.cross(up_vector);
+        let goal_dir = (goal_dir - up_vector * goal_dir.dot(up_vector)).normalize_or_zero();
+
+        let forward_align = goal_dir.dot(forward);
+        let right_align = goal_dir.dot(right);
+        let forward_scale = if forward_align < 0.0 {
+            self.backward_speed_scale
+        } else {
+            1.0
+        };
+
+        if forward_align == 0.0 && right_align == 0.0 {
+            return 1.0;
+        }
+
+        // Interpolate along an ellipse so diagonals blend between the axis speeds.
+        ((forward_align * forward_scale).powi(2) + (right_align * self.strafe_speed_scale).powi(2))
+            .sqrt()
+    }
+
+    /// Speed multiplier for moving in `goal_dir` along ground with `normal`.
+    pub fn slope_speed_scale(
+        &self,
+        up_vector: Vec3,
+        normal: Vec3,
+        max_ground_angle: f32,
+        goal_dir: Vec3,
+    ) -> f32 {
+        if max_ground_angle <= 0.0 {
+            return 1.0;
+        }
+
+        let steepness = (normal.angle_between(up_vector) / max_ground_angle).clamp(0.0, 1.0);
+        let downhill = normal - up_vector * normal.dot(up_vector);
+        let alignment = goal_dir.dot(downhill.normalize_or_zero());
+
+        let (scale, alignment) = if alignment < 0.0 {
+            (self.uphill_speed_scale, -alignment)
+        } else {
+            (self.downhill_speed_scale, alignment)
+        };
+
+        1.0 + (scale - 1.0) * steepness * alignment
+    }
+
+    /// Clamp `force` to [`max_acceleration_force`](Self::max_acceleration_force).
+    pub fn cap_force(&self, force: Vec3) -> Vec3 {
+        match self.max_acceleration_force {
+            Some(max) => force.clamp_length_max(max.max(0.0)),
+            None => force,
+        }
+    }
+
+    /// Remove the parts of `force` pushing into any of the wall `normals`, see [`Movement::slide_along_walls`].
+    pub fn clip_force<I>(force: Vec3, normals: I) -> Vec3
+    where
+        I: IntoIterator<Item = Vec3>,
+        I::IntoIter: Clone,
+    {
+        Self::slide_along_walls(force, normals)
+    }
+
+    /// Project `vector` onto the walls with `normals` it points into, so it slides along them.
+    ///
+    /// Between two walls (e.g. a corner) this slides along the crease where they meet, if that
+    /// still points into a wall then nothing is left.
+    pub fn slide_along_walls<I>(vector: Vec3, normals: I) -> Vec3
+    where
+        I: IntoIterator<Item = Vec3>,
+        I::IntoIter: Clone,
+    {
+        let normals = normals.into_iter();
+        let mut slid = vector;
+        for (index, normal) in normals.clone().enumerate() {
+            let into = slid.dot(normal);
+            if into >= 0.0 {
+                continue;
+            }
+
+            slid -= normal * into;
+
+            // Sliding along this wall might push into a previous one, follow the crease between them.
+            for previous in normals.clone().take(index) {
+                if slid.dot(previous) >= -1e-4 {
+                    continue;
+                }
+
+                let crease = previous.cross(normal);
+                if crease.length_squared() > 1e-6 {
+                    let crease = crease.normalize();
+                    slid = crease * vector.dot(crease);
+                }
+            }
+        }
+
+        if normals.clone().any(|normal| slid.dot(normal) < -1e-4) {
+            Vec3::ZERO
+        } else {
+            slid
+        }
+    }
+
     /// Calculate force scale.
     pub fn force_scale(&self, gravity: &Gravity) -> Vec3 {
         match self.force_scale {
@@ -84,250 +377,350 @@ pub fn movement_force(
         &ViableGroundCast,
         &ControllerVelocity,
         &ControllerMass,
+        &Grounded,
+        &GroundCaster,
+        &Upright,
+        Option<&WallJump>,
+        (Option<&WallCaster>, Option<&ActionGates>, Option<&Slide>),
     )>,
-    globals: Query<&GlobalTransform>,
-    masses: Query<&ReadMassProperties>,
     frictions: Query<&Friction>,
+    bodies: Query<&RigidBody>,
+    surfaces: Query<&SurfaceProperties>,
     //mut gizmos: Gizmos,
 ) {
     let dt = ctx.integration_parameters.dt;
-    for (
-        controller_entity,
-        mut force,
-        movement,
-        gravity,
-        input,
-        ground,
-        viable_ground,
-        velocity,
-        mass,
-    ) in &mut query
-    {
-        force.linear = Vec3::ZERO;
+    query.par_iter_mut().for_each_mut(
+        |(
+            controller_entity,
+            mut force,
+            mut movement,
+            gravity,
+            input,
+            ground,
+            viable_ground,
+            velocity,
+            mass,
+            grounded,
+            ground_caster,
+            upright,
+            wall_jump,
+            (wall_caster, gates, slide),
+        )| {
+            force.linear = Vec3::ZERO;
+
+            let can_sprint = gates.map(|gates| gates.can_sprint).unwrap_or(true);
+            movement.update_target_speed(input.sprinting && can_sprint, dt);
+
+            // Keep up with the ground's acceleration so we don't slide off of moving platforms.
+            let carry = match viable_ground.current() {
+                Some(ground)
+                    if movement.carry_with_ground
+                        && **grounded
+                        && !matches!(bodies.get(ground.entity), Ok(RigidBody::Dynamic)) =>
+                {
+                    Some((ground.entity, ground.point_velocity))
+                }
+                _ => None,
+            };
+
+            if let (Some((entity, velocity)), Some((last_entity, last_velocity))) =
+                (carry, movement.last_carry_velocity)
+            {
+                if entity == last_entity {
+                    force.linear += (velocity - last_velocity) * mass.mass / dt;
+                }
+            }
+            movement.last_carry_velocity = carry;
+
+            // Let the controller get away from the wall it jumped off of.
+            if wall_jump.is_some_and(WallJump::locked_out) {
+                return;
+            }
+
+            let force_scale = movement.force_scale(&gravity);
+
+            let surface = match viable_ground.current() {
+                Some(ground) if **grounded => surfaces.get(ground.entity).ok().cloned(),
+                _ => None,
+            }
+            .unwrap_or_default();
+            let target_speed = movement.target_speed() * surface.max_speed_scale;
+            let slide = slide.filter(|slide| slide.sliding);
+
+            let input_dir = movement.apply_deadzone(input.movement);
+            let mut goal_vel = input_dir * target_speed;
+
+            if let Some(forward) = upright.forward_vector {
+                goal_vel *= movement.directional_speed_scale(gravity.up_vector, forward, input_dir);
+            }
 
-        let force_scale = movement.force_scale(&gravity);
+            if let (Some(ground), true) = (viable_ground.current(), **grounded) {
+                goal_vel *= movement.slope_speed_scale(
+                    gravity.up_vector,
+                    ground.cast.normal,
+                    ground_caster.max_ground_angle,
+                    goal_vel.normalize_or_zero(),
+                );
+            }
 
-        let input_dir = input.movement.clamp_length_max(1.0);
-        let mut goal_vel = input_dir * movement.max_speed;
+            let slip_vector = match ground.current() {
+                Some(ground) if !ground.stable => {
+                    let down_tangent = ground.cast.down_tangent(gravity.up_vector);
+                    let slip_vector = (down_tangent * force_scale).normalize_or_zero();
 
-        let slip_vector = match ground.current() {
-            Some(ground) if !ground.stable => {
-                let down_tangent = ground.cast.down_tangent(gravity.up_vector);
-                let slip_vector = (down_tangent * force_scale).normalize_or_zero();
+                    // Counteract the movement going up the slope.
+                    let alignment = goal_vel.dot(slip_vector);
+                    if alignment < 0.0 {
+                        let slip_goal = alignment * slip_vector;
+                        goal_vel -= slip_goal * movement.slip_force_scale;
+                    }
 
-                // Counteract the movement going up the slope.
-                let alignment = goal_vel.dot(slip_vector);
-                if alignment < 0.0 {
-                    let slip_goal = alignment * slip_vector;
-                    goal_vel -= slip_goal * movement.slip_force_scale;
+                    // Pushing to force the controller down the slope
+                    Some(slip_vector)
                 }
+                _ => None,
+            };
 
-                // Pushing to force the controller down the slope
-                Some(slip_vector)
+            let slip_force = -(slip_vector.unwrap_or(Vec3::ZERO)) * mass.mass;
+
+            // Gravity already slides us down ground that is too steep to stand on, resist some of it.
+            if let Some(ground) = ground.current() {
+                if !ground.viable && ground.cast.normal.length_squared() > 0.0 {
+                    let gravity_accel = gravity.up_vector * gravity.acceleration;
+                    let along_slope =
+                        gravity_accel - ground.cast.normal * gravity_accel.dot(ground.cast.normal);
+                    let friction = movement.slide_friction.clamp(0.0, 1.0);
+                    force.linear -= along_slope * friction * mass.mass;
+                }
             }
-            _ => None,
-        };
 
-        let slip_force = -(slip_vector.unwrap_or(Vec3::ZERO)) * mass.mass;
+            // Velocity of the ground right where we're standing on it, so standing off-center
+            // on a spinning platform doesn't drift.
+            let last_ground_vel = viable_ground
+                .last()
+                .map_or(Vec3::ZERO, |ground| ground.point_velocity);
 
-        let last_ground_vel = if let Some(ground) = viable_ground.last() {
-            let ground_global = globals
-                .get(ground.entity)
-                .unwrap_or(&GlobalTransform::IDENTITY);
+            // Follow the slope of the ground rather than moving flat into/off of it.
+            let ground_normal = match viable_ground.current() {
+                Some(ground) if movement.project_on_ground && **grounded && ground.stable => {
+                    Some(ground.cast.normal)
+                }
+                _ => None,
+            };
 
-            let ground_mass = if let Ok(mass) = masses.get(ground.entity) {
-                mass.0.clone()
+            let force_scale = if let Some(normal) = ground_normal {
+                let along_ground = goal_vel - normal * goal_vel.dot(normal);
+                goal_vel = along_ground.normalize_or_zero() * goal_vel.length();
+                Vec3::ONE
             } else {
-                MassProperties::default()
+                force_scale
             };
 
-            let com = ground_global.transform_point(ground_mass.local_center_of_mass);
-            let projected_angular = ground.angular_velocity.project_onto(gravity.up_vector);
-            ground.linear_velocity + projected_angular.cross(ground.cast.point - com)
-        } else {
-            Vec3::ZERO
-        };
+            let walls = wall_caster.map_or(&[][..], |caster| &caster.contacts[..]);
+            let wall_normals = walls.iter().map(|wall| wall.cast.normal);
 
-        let relative_velocity = (velocity.linear - last_ground_vel) * force_scale;
-        let friction_coefficient = if let Some(ground) = viable_ground.current() {
-            let friction = frictions
-                .get(controller_entity)
-                .copied()
-                .unwrap_or(Friction::default());
-            let ground_friction = frictions
-                .get(ground.entity)
-                .copied()
-                .unwrap_or(Friction::default());
-            let friction_coefficient = friction.coefficient.max(ground_friction.coefficient);
-            friction_coefficient
-        } else {
-            // Air damping coefficient
-            0.25
-        };
+            if movement.wall_slide && !walls.is_empty() {
+                let slid = Movement::slide_along_walls(goal_vel, wall_normals.clone());
+                let retention = movement.wall_slide_speed_retention.clamp(0.0, 1.0);
+                let speed = slid.length() + (goal_vel.length() - slid.length()) * retention;
+                goal_vel = slid.normalize_or_zero() * speed;
+            }
 
-        let strength = movement.acceleration.get(mass.mass, dt);
-        let movement_force = goal_vel * strength * force_scale;
+            // Let gravity carry the slide down slopes.
+            if let (Some(slide), Some(ground), true) = (slide, viable_ground.current(), **grounded)
+            {
+                let normal = ground.cast.normal;
+                let gravity_accel = gravity.up_vector * gravity.acceleration;
+                let along_slope = gravity_accel - normal * gravity_accel.dot(normal);
+                force.linear += along_slope * slide.slope_acceleration * mass.mass;
+            }
 
-        let mut friction_velocity = relative_velocity;
-        let goal_dir = goal_vel.normalize_or_zero();
-        let goal_align = relative_velocity.dot(goal_dir);
+            let mut relative_velocity = (velocity.linear - last_ground_vel) * force_scale;
+            if let Some(normal) = ground_normal {
+                relative_velocity -= normal * relative_velocity.dot(normal);
+            }
 
-        let difference = (goal_vel.length() - goal_align.max(0.0)).max(0.0);
-        let displacement = difference * goal_dir;
+            if movement.air_strafe && !**grounded {
+                let air_strafe =
+                    movement.air_strafe_force(relative_velocity, goal_vel, mass.mass, dt);
+                force.linear += movement.cap_force(air_strafe);
+                return;
+            }
 
-        let max_movement_force = displacement * mass.mass / dt * force_scale;
-        let movement_force = movement_force.clamp_length_max(max_movement_force.length());
+            let friction_coefficient = if let Some(ground) = viable_ground.current() {
+                let friction = frictions
+                    .get(controller_entity)
+                    .copied()
+                    .unwrap_or(Friction::default());
+                let ground_friction = frictions
+                    .get(ground.entity)
+                    .copied()
+                    .unwrap_or(Friction::default());
+                let friction_coefficient = friction.coefficient.max(ground_friction.coefficient);
+                let slide_friction = slide.map_or(1.0, |slide| slide.friction);
+                friction_coefficient * surface.friction_scale * slide_friction
+            } else {
+                // Air damping coefficient
+                0.25
+            };
 
-        let friction_align = goal_align;
-        let friction_offset = friction_align.clamp(0.0, goal_vel.length());
-        friction_velocity -= friction_offset * goal_dir;
+            let air_control = if **grounded {
+                1.0
+            } else {
+                movement.air_control
+            };
 
-        let friction_strength = Strength::Scaled(friction_coefficient.clamp(0.0, 1.0) * 45.0);
-        let friction_force = friction_velocity * friction_strength.get(mass.mass, dt) * force_scale;
+            let mut friction_velocity = relative_velocity;
+            let goal_dir = goal_vel.normalize_or_zero();
+            let goal_align = relative_velocity.dot(goal_dir);
 
-        /*
-        let squish = 0.2;
-        gizmos.ray(Vec3::ZERO, goal_vel * squish, Color::GREEN);
-        gizmos.sphere(goal_align * goal_dir * squish, Quat::IDENTITY, 0.1, Color::GREEN);
-        gizmos.ray(Vec3::ZERO, relative_velocity * squish, Color::BLUE);
-        gizmos.ray(relative_velocity * squish, -goal_offset * goal_dir * squish, Color::RED);
-        gizmos.ray(Vec3::new(0.0, 0.1, 0.0), friction_velocity * squish, Color::CYAN);
-        */
+            let speed_progress = if goal_vel.length() > 0.0 {
+                (goal_align / goal_vel.length()).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let strength = movement
+                .acceleration
+                .get_with_progress(speed_progress, mass.mass, dt)
+                * air_control
+                * surface.accel_scale
+                * slide.map_or(1.0, |slide| slide.steering);
+            let movement_force = goal_vel * strength * force_scale;
+
+            let difference = (goal_vel.length() - goal_align.max(0.0)).max(0.0);
+            let displacement = difference * goal_dir;
 
-        force.linear += movement_force - friction_force - slip_force;
+            let max_movement_force = displacement * mass.mass / dt * force_scale;
+            let speed = relative_velocity.length();
+            let movement_force = if speed > target_speed {
+                // We are already going faster than we can move on our own (e.g. launched by something),
+                // so don't add any more speed but still allow steering.
+                let velocity_dir = relative_velocity / speed;
+                movement_force - velocity_dir * movement_force.dot(velocity_dir).max(0.0)
+            } else {
+                movement_force.clamp_length_max(max_movement_force.length())
+            };
+
+            let movement_force = if movement.clip_to_walls && !walls.is_empty() {
+                Movement::clip_force(movement_force, wall_normals)
+            } else {
+                movement_force
+            };
+
+            let friction_align = goal_align;
+            let friction_offset = friction_align.clamp(0.0, goal_vel.length());
+            friction_velocity -= friction_offset * goal_dir;
+
+            let friction_strength = Strength::Scaled(friction_coefficient.clamp(0.0, 1.0) * 45.0);
+            let friction_force =
+                friction_velocity * friction_strength.get(mass.mass, dt) * force_scale;
+
+            /*
+            let squish = 0.2;
+            gizmos.ray(Vec3::ZERO, goal_vel * squish, Color::GREEN);
+            gizmos.sphere(goal_align * goal_dir * squish, Quat::IDENTITY, 0.1, Color::GREEN);
+            gizmos.ray(Vec3::ZERO, relative_velocity * squish, Color::BLUE);
+            gizmos.ray(relative_velocity * squish, -goal_offset * goal_dir * squish, Color::RED);
+            gizmos.ray(Vec3::new(0.0, 0.1, 0.0), friction_velocity * squish, Color::CYAN);
+            */
+
+            let braking = **grounded && goal_vel.length_squared() == 0.0 && slide.is_none();
+            let braking_force = if braking {
+                // Only brake whatever velocity friction hasn't already taken care of.
+                let remaining_velocity = friction_velocity - friction_force * dt / mass.mass;
+                movement.deceleration_force(remaining_velocity, mass.mass, dt)
+                    * surface.friction_scale.clamp(0.0, 1.0)
+            } else {
+                Vec3::ZERO
+            };
+
+            force.linear +=
+                movement.cap_force(movement_force - friction_force - slip_force + braking_force);
+        },
+    );
+}
+
+/// Sent when a controller starts or stops moving, see [`Movement::moving_speed_threshold`].
+#[derive(Event, Debug, Clone)]
+pub struct MovementStateEvent {
+    /// Controller that started/stopped moving.
+    pub entity: Entity,
+    /// Did the controller start moving, otherwise it stopped.
+    pub moving: bool,
+    /// Horizontal speed of the controller relative to the ground.
+    pub speed: f32,
+}
+
+/// Track whether controllers are moving and send [`MovementStateEvent`]s.
+pub fn movement_state(
+    mut query: Query<(
+        Entity,
+        &mut Movement,
+        &Gravity,
+        &ControllerVelocity,
+        &GroundContact,
+    )>,
+    mut events: EventWriter<MovementStateEvent>,
+) {
+    for (entity, mut movement, gravity, velocity, contact) in &mut query {
+        let speed = gravity
+            .project(velocity.linear - contact.surface_velocity)
+            .length();
+
+        let moving = if movement.moving {
+            speed > movement.stopped_speed_threshold
+        } else {
+            speed > movement.moving_speed_threshold
+        };
+
+        if moving != movement.moving {
+            movement.moving = moving;
+            events.send(MovementStateEvent {
+                entity,
+                moving,
+                speed,
+            });
+        }
     }
 }
 
-/// How the controller's jumping should behave.
+/// Jumping off of walls while in the air.
+///
+/// Requires a [`WallCaster`] and [`WallCast`] to find walls to jump off of.
+/// Wall jumps take priority over air jumps and don't use up any of [`Jump::jumps`].
 #[derive(Component, Debug, Clone, Reflect)]
 #[reflect(Component, Default)]
-pub struct Jump {
-    /// The amount of force to apply on the first frame when a jump begins.
-    pub initial_force: f32,
-    /// The amount of force to continuously apply every second during a jump.
-    pub force: f32,
-    /// How long to wait before we can jump again.
-    pub cooldown_duration: f32,
-    /// Timer for tracking `cooldown_duration`.
-    pub cooldown_timer: f32,
-    /// How long a jump can last.
-    pub jump_duration: f32,
-    /// Timer for tracking `jump_duration`.
-    pub jump_timer: f32,
-    /// A function taking the current progress of a jump, from 0.0 to 1.0, with 0.0 indicating a jump has just begun and 1.0 indicating the jump has ended,
-    /// which returns a modifier (usually from 0.0 to 1.0, but not necessarily) to multiply [`jump_force`](ControllerSettings::jump_force) by.
-    #[reflect(ignore)]
-    pub decay_function: Option<fn(f32) -> f32>,
-
-    /// Number of times we can jump before we have to touch the ground again.
-    pub jumps: u32,
-    /// Remaining before we have to touch the ground again.
-    pub remaining_jumps: u32,
-    /// Was [`ControllerInput::jumping`] true last frame.
-    pub pressed_last_frame: bool,
-    /// The amount of force to apply downwards when the jump control is released prior to a jump expiring.
-    /// This allows analog jumping by cutting the jump short when the control is released.
-    pub stop_force: f32,
-
-    /// A timer to track jump buffering. See [`jump_buffer_duration`](ControllerSettings::jump_buffer_duration)
-    pub buffer_timer: f32,
-    /// If the jump input is pressed before landing, how long will the jump be buffered for?
-    /// In other words, if this is 0.5, the character can input jump up to 0.5 seconds
-    /// before landing and the jump will occur when they land.
-    pub buffer_duration: f32,
-
-    /// Do we have to be grounded to jump for the first time?
-    pub first_jump_grounded: bool,
-    /// How long should the character still be able to jump after leaving the ground, in seconds.
-    /// For example, if this is set to 0.5, the player can fall off a ledge and then jump if they do so within 0.5 seconds of leaving the ledge.
-    pub coyote_duration: f32,
-    /// A timer to track coyote time. See [`coyote_duration`](Self::coyote_duration)
-    pub coyote_timer: f32,
-
-    /// How long to skip ground checks after jumping. Usually this should be set just high enough that the character is out of range of the ground
-    /// just before the timer elapses.
-    pub skip_ground_check_duration: f32,
+pub struct WallJump {
+    /// The amount of force to apply away from the wall when a wall jump begins.
+    pub away_force: f32,
+    /// The amount of force to apply upwards when a wall jump begins.
+    pub up_force: f32,
+    /// How long to ignore movement input after a wall jump, so the controller actually leaves the wall.
+    pub lockout_duration: f32,
+    /// Timer for tracking `lockout_duration`.
+    pub lockout_timer: f32,
 }
 
-impl Default for Jump {
+impl Default for WallJump {
     fn default() -> Self {
         Self {
-            initial_force: 30.0,
-            force: 20.0,
-            cooldown_duration: 0.25,
-            cooldown_timer: 0.0,
-            jump_duration: 0.1,
-            jump_timer: 0.0,
-            decay_function: Some(|x| (1.0 - x).sqrt()),
-            stop_force: 0.3,
-
-            buffer_duration: 0.3,
-            buffer_timer: 0.0,
-
-            first_jump_grounded: true,
-            coyote_duration: 0.2,
-            coyote_timer: 0.0,
-
-            jumps: 1,
-            remaining_jumps: 1,
-            pressed_last_frame: false,
-
-            skip_ground_check_duration: 0.0,
+            away_force: 20.0,
+            up_force: 30.0,
+            lockout_duration: 0.2,
+            lockout_timer: 0.0,
         }
     }
 }
 
-impl Jump {
+impl WallJump {
     /// Tick down timers by `dt`/delta time.
     pub fn tick_timers(&mut self, dt: f32) {
-        let tick = |timer: &mut f32| {
-            if *timer > 0.0 {
-                *timer = (*timer - dt).max(0.0);
-            }
-        };
-
-        tick(&mut self.cooldown_timer);
-        tick(&mut self.jump_timer);
-        tick(&mut self.buffer_timer);
-        tick(&mut self.coyote_duration);
-    }
-
-    /// Are we currently jumping?
-    pub fn jumping(&self) -> bool {
-        self.jump_timer > 0.0
-    }
-
-    /// Can we jump right now?
-    pub fn can_jump(&self, grounded: bool) -> bool {
-        let first_jump = self.remaining_jumps == self.jumps;
-        //info!("first_jump: {:?}", first_jump);
-        let grounded = grounded || self.coyote_timer > 0.0;
-        //info!("grounded: {:?}", grounded);
-        if first_jump && !grounded {
-            return false;
-        }
-
-        self.cooldown_timer <= 0.0 && self.remaining_jumps > 0
-    }
-
-    /// Reset the jumping state.
-    pub fn reset_jump(&mut self) {
-        self.remaining_jumps = self.jumps;
-        self.jump_timer = 0.0;
-    }
-
-    /// 0..1 progress of the current jump.
-    pub fn jump_progress(&self) -> f32 {
-        (self.jump_duration - self.jump_timer) / self.jump_duration
+        self.lockout_timer = (self.lockout_timer - dt).max(0.0);
     }
 
-    /// Jump force decay multiplier.
-    pub fn decay_multiplier(&self) -> f32 {
-        if let Some(decay_function) = self.decay_function {
-            (decay_function)(self.jump_progress())
-        } else {
-            1.0
-        }
+    /// Is movement input currently being ignored after a wall jump?
+    pub fn locked_out(&self) -> bool {
+        self.lockout_timer > 0.0
     }
 }
 
@@ -339,25 +732,71 @@ pub struct JumpForce {
     pub linear: Vec3,
 }
 
+/// What kind of jump was done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum JumpKind {
+    /// Jumped while grounded.
+    Ground,
+    /// Jumped shortly after leaving the ground, see [`Jump::coyote_duration`].
+    Coyote,
+    /// Jumped in the air, see [`Jump::jumps`].
+    Air,
+    /// Jumped off of a wall, see [`WallJump`].
+    Wall,
+}
+
+/// Sent when a controller starts a jump.
+#[derive(Event, Debug, Clone)]
+pub struct JumpEvent {
+    /// The controller that jumped.
+    pub entity: Entity,
+    /// World-space impulse applied to the controller at the start of the jump.
+    pub impulse: Vec3,
+    /// The entity we jumped off of, if any.
+    ///
+    /// For [`JumpKind::Wall`] this is the wall.
+    pub from_ground: Option<Entity>,
+    /// What kind of jump this was.
+    pub kind: JumpKind,
+}
+
+/// Sent once per jump when the controller stops rising, at the top of the jump.
+#[derive(Event, Debug, Clone)]
+pub struct ApexEvent {
+    /// The controller that reached the apex.
+    pub entity: Entity,
+    /// Height of the apex above where the jump started, along the up vector.
+    pub height: f32,
+}
+
+/// State used to calculate [`JumpForce`].
+type JumpForceQuery = (
+    Entity,
+    &'static mut JumpForce,
+    &'static mut FloatForce,
+    &'static mut GravityForce,
+    &'static mut Jump,
+    &'static ControllerInput,
+    &'static mut GroundCaster,
+    &'static ViableGroundCast,
+    &'static Grounded,
+    &'static Gravity,
+    &'static ControllerVelocity,
+    &'static ControllerMass,
+    Option<&'static mut WallJump>,
+    Option<&'static WallCast>,
+    Option<&'static ActionGates>,
+);
+
 /// Calculate the jump force for the controller.
 pub fn jump_force(
-    mut query: Query<(
-        &mut JumpForce,
-        &mut FloatForce,
-        &mut GravityForce,
-        &mut Jump,
-        &ControllerInput,
-        &mut GroundCaster,
-        &ViableGroundCast,
-        &Grounded,
-        &Gravity,
-        &ControllerVelocity,
-        &ControllerMass,
-    )>,
+    mut query: Query<JumpForceQuery, Simulated>,
     ctx: Res<RapierContext>,
+    mut jump_events: EventWriter<JumpEvent>,
 ) {
     let dt = ctx.integration_parameters.dt;
     for (
+        entity,
         mut force,
         mut float_force,
         mut gravity_force,
@@ -369,65 +808,385 @@ pub fn jump_force(
         gravity,
         velocity,
         mass,
+        mut wall_jump,
+        wall_cast,
+        gates,
     ) in &mut query
     {
         force.linear = Vec3::ZERO;
 
         let grounded = **grounded;
         jumping.tick_timers(dt);
-
-        if grounded {
-            jumping.coyote_timer = jumping.coyote_duration;
+        let jump_wanted = jumping.update(input.jumping, grounded);
+        if let Some(wall_jump) = wall_jump.as_deref_mut() {
+            wall_jump.tick_timers(dt);
         }
 
-        if jumping.cooldown_timer <= 0.0 && grounded {
-            jumping.reset_jump();
-        }
+        let ground_velocity = if let Some(ground) = viable_ground.last() {
+            ground.point_velocity
+        } else {
+            Vec3::ZERO
+        };
+        let velocity = velocity.linear - ground_velocity;
 
-        let velocity = if let Some(ground) = viable_ground.last() {
-            velocity.linear - ground.point_velocity
+        // Blocked jumps stay buffered, in case the gate opens back up in time.
+        let can_jump = gates.map(|gates| gates.can_jump).unwrap_or(true);
+        let just_jumped = jump_wanted && can_jump;
+
+        // Walls are only for jumping off of while we are in the air.
+        let wall = if !grounded && !jumping.in_coyote_time() {
+            wall_cast.and_then(|wall_cast| wall_cast.0)
         } else {
-            velocity.linear
+            None
         };
+        let can_wall_jump = just_jumped && !jumping.on_cooldown();
 
-        let jump_inputted = input.jumping && !jumping.pressed_last_frame;
+        if let (Some(wall), Some(wall_jump), true) = (wall, wall_jump.as_deref_mut(), can_wall_jump)
+        {
+            let up = gravity.up_vector;
+            let away = (wall.cast.normal - up * wall.cast.normal.dot(up)).normalize_or_zero();
 
-        let just_jumped = jump_inputted || jumping.buffer_timer > 0.0;
+            // Same as regular jumps, negate velocity going up/into the wall for consistency.
+            let into_wall = away * velocity.dot(away).min(0.0);
+            let negate_velocity = -(up * velocity.dot(up) + into_wall) * mass.mass / dt;
+            let scale = jumping.force_scale(mass.mass);
+            let wall_jump_force = (up * wall_jump.up_force + away * wall_jump.away_force) * scale;
+            force.linear += negate_velocity + wall_jump_force;
 
-        if jump_inputted && !grounded {
-            jumping.buffer_timer = jumping.buffer_duration;
-        }
+            gravity_force.linear = Vec3::ZERO;
+            float_force.linear = Vec3::ZERO;
+
+            jumping.cooldown_timer = jumping.cooldown_duration;
+            jumping.buffer_timer = 0.0;
+            jumping.jump_timer = jumping.jump_duration;
+            wall_jump.lockout_timer = wall_jump.lockout_duration;
+
+            jump_events.send(JumpEvent {
+                entity,
+                impulse: force.linear * dt,
+                from_ground: Some(wall.entity),
+                kind: JumpKind::Wall,
+            });
+        } else if jumping.can_jump(grounded) && just_jumped {
+            let kind = if grounded {
+                JumpKind::Ground
+            } else if jumping.first_jump() && jumping.in_coyote_time() {
+                JumpKind::Coyote
+            } else {
+                JumpKind::Air
+            };
+
+            let jump_direction = match viable_ground.current() {
+                Some(ground) if grounded => {
+                    jumping.direction(gravity.up_vector, ground.cast.normal)
+                }
+                _ => gravity.up_vector,
+            };
 
-        if jumping.can_jump(grounded) && just_jumped {
             // Negating the current velocity increases consistency for falling jumps,
             // and prevents stacking jumps to reach high upwards velocities
-            let initial_jump_force = jumping.initial_force * gravity.up_vector;
+            let initial_jump_force = match jumping.desired_velocity(gravity.acceleration) {
+                Some(desired_velocity) => jump_direction * desired_velocity * mass.mass / dt,
+                None => jumping.initial_force * jumping.force_scale(mass.mass) * jump_direction,
+            };
             let negate_up_velocity =
                 (-1.0 * gravity.up_vector * velocity.dot(gravity.up_vector)) * mass.mass / dt;
             force.linear += negate_up_velocity + initial_jump_force;
 
+            if kind == JumpKind::Air && jumping.air_jump_redirect > 0.0 {
+                let horizontal = gravity.project(velocity);
+                let redirected =
+                    jumping.redirect_velocity(horizontal, gravity.project(input.movement));
+                force.linear += (redirected - horizontal) * mass.mass / dt;
+            }
+
+            // Our velocity is already relative to the ground, so to not inherit
+            // the ground's velocity we have to cancel it out.
+            if !jumping.inherit_ground_velocity {
+                force.linear -= ground_velocity * mass.mass / dt;
+            }
+
             gravity_force.linear = Vec3::ZERO;
             float_force.linear = Vec3::ZERO;
 
-            jumping.remaining_jumps = jumping.remaining_jumps.saturating_sub(1);
-            jumping.cooldown_timer = jumping.cooldown_duration;
+            jumping.start();
 
-            jumping.jump_timer = jumping.jump_duration;
+            jump_events.send(JumpEvent {
+                entity,
+                impulse: force.linear * dt,
+                from_ground: match kind {
+                    JumpKind::Air => None,
+                    _ => viable_ground.last().map(|ground| ground.entity),
+                },
+                kind,
+            });
         // don't double up on initial force and jumping forces.
         } else if jumping.jumping() {
             if !input.jumping {
                 // Cut the jump short if we aren't holding the jump down.
                 //jumping.reset_jump();
-                let stop_force = velocity.project_onto(gravity.up_vector) * -jumping.stop_force;
-                force.linear += stop_force;
+                // Only while ascending, otherwise we'd be pushing the controller back up.
+                if velocity.dot(gravity.up_vector) > 0.0 {
+                    let stop_force = velocity.project_onto(gravity.up_vector)
+                        * -jumping.stop_force
+                        * jumping.force_scale(mass.mass);
+                    force.linear += stop_force;
+                }
             } else {
                 ground_caster.skip_ground_check_timer = jumping.skip_ground_check_duration;
 
-                let jump = gravity.up_vector * jumping.force * jumping.decay_multiplier();
-                force.linear += jump;
+                // The initial velocity already reaches the desired height on its own.
+                if jumping.desired_height.is_none() {
+                    let jump = gravity.up_vector
+                        * jumping.force
+                        * jumping.force_scale(mass.mass)
+                        * jumping.decay_multiplier();
+                    force.linear += jump;
+                }
             }
         }
+    }
+}
+
+/// Send an [`ApexEvent`] when a jumping controller's vertical velocity stops being positive.
+pub fn jump_apex(
+    mut query: Query<(
+        Entity,
+        &mut Jump,
+        &GlobalTransform,
+        &Gravity,
+        &ControllerVelocity,
+        &Grounded,
+    )>,
+    mut jump_events: EventReader<JumpEvent>,
+    mut apex_events: EventWriter<ApexEvent>,
+) {
+    // The jump impulse hasn't been applied to the velocity yet, so wait until next frame.
+    let mut jumped = Vec::new();
+    for event in jump_events.iter() {
+        let Ok((_, mut jump, tf, ..)) = query.get_mut(event.entity) else { continue };
+        jump.apex_origin = Some(tf.translation());
+        jumped.push(event.entity);
+    }
+
+    for (entity, mut jump, tf, gravity, velocity, grounded) in &mut query {
+        let Some(origin) = jump.apex_origin else { continue };
+        if jumped.contains(&entity) || velocity.vertical_speed(gravity.up_vector) > 0.0 {
+            continue;
+        }
+
+        jump.apex_origin = None;
+
+        // Landing on something while still rising isn't an apex.
+        if !**grounded {
+            apex_events.send(ApexEvent {
+                entity,
+                height: (tf.translation() - origin).dot(gravity.up_vector),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadzone_zeroes_small_input() {
+        let movement = Movement {
+            input_deadzone: 0.2,
+            ..default()
+        };
+
+        assert_eq!(movement.apply_deadzone(Vec3::ZERO), Vec3::ZERO);
+        assert_eq!(movement.apply_deadzone(Vec3::X * 0.2), Vec3::ZERO);
+        assert_eq!(
+            movement.apply_deadzone(Vec3::new(0.1, 0.0, -0.1)),
+            Vec3::ZERO
+        );
+    }
+
+    #[test]
+    fn deadzone_rescales_remaining_range() {
+        let movement = Movement {
+            input_deadzone: 0.2,
+            ..default()
+        };
+
+        // Just past the deadzone starts from nothing, full input stays full.
+        let halfway = movement.apply_deadzone(Vec3::Z * 0.6);
+        assert!((halfway - Vec3::Z * 0.5).length() < 1e-5);
+        let full = movement.apply_deadzone(Vec3::Z);
+        assert!((full - Vec3::Z).length() < 1e-5);
+
+        // Direction is kept, and oversized input is clamped.
+        let diagonal = movement.apply_deadzone(Vec3::new(3.0, 0.0, 4.0));
+        assert!((diagonal - Vec3::new(0.6, 0.0, 0.8)).length() < 1e-5);
+    }
+
+    #[test]
+    fn no_deadzone_passes_input_through() {
+        let movement = Movement::default();
+        let input = Vec3::new(0.3, 0.0, 0.4);
+        assert!((movement.apply_deadzone(input) - input).length() < 1e-6);
+    }
+
+    #[test]
+    fn air_strafe_caps_wish_speed() {
+        let movement = Movement::default();
+        let (mass, dt) = (2.0, 0.1);
+
+        // Only `air_speed_cap` can be gained along the input direction.
+        let force = movement.air_strafe_force(Vec3::ZERO, Vec3::X * 5.0, mass, dt);
+        assert!((force - Vec3::X * movement.air_speed_cap * mass / dt).length() < 1e-4);
+
+        // Already moving at the cap, nothing more is added.
+        let force = movement.air_strafe_force(Vec3::X, Vec3::X * 5.0, mass, dt);
+        assert_eq!(force, Vec3::ZERO);
+    }
+
+    #[test]
+    fn air_strafe_accelerates_perpendicular_to_velocity() {
+        let movement = Movement {
+            air_strafe_acceleration: 1.0,
+            ..default()
+        };
+        let (mass, dt) = (1.0, 0.1);
+
+        // Strafing sideways is unaffected by how fast we're already going.
+        let force = movement.air_strafe_force(Vec3::X * 10.0, Vec3::Z * 5.0, mass, dt);
+        assert!((force - Vec3::Z * 5.0).length() < 1e-4);
+
+        // No input, nothing to strafe towards.
+        let force = movement.air_strafe_force(Vec3::X * 10.0, Vec3::ZERO, mass, dt);
+        assert_eq!(force, Vec3::ZERO);
+    }
+
+    #[test]
+    fn slide_along_single_wall() {
+        let slid = Movement::slide_along_walls(Vec3::new(-1.0, 0.0, 1.0), [Vec3::X]);
+        assert!((slid - Vec3::Z).length() < 1e-5);
+
+        // Moving away from a wall isn't affected by it.
+        let away = Vec3::new(1.0, 0.0, 1.0);
+        assert_eq!(Movement::slide_along_walls(away, [Vec3::X]), away);
+    }
+
+    #[test]
+    fn slide_into_right_angle_corner() {
+        let walls = [Vec3::X, Vec3::Z];
+        let slid = Movement::slide_along_walls(Vec3::new(-1.0, 1.0, -1.0), walls);
+        assert!((slid - Vec3::Y).length() < 1e-5);
+    }
+
+    #[test]
+    fn slide_along_crease_of_acute_corner() {
+        let walls = [Vec3::X, Vec3::new(-1.0, 0.0, 1.0).normalize()];
+
+        // Sliding off the second wall pushes back into the first, so follow the crease instead.
+        let slid = Movement::slide_along_walls(Vec3::new(-1.0, 1.0, -0.5), walls);
+        assert!((slid - Vec3::Y).length() < 1e-5);
+        for wall in walls {
+            assert!(slid.dot(wall) >= -1e-4);
+        }
+
+        // Nothing is left when pushing straight into the corner.
+        let stuck = Movement::slide_along_walls(Vec3::new(-1.0, 0.0, -0.5), walls);
+        assert!(stuck.length() < 1e-5);
+    }
+
+    #[test]
+    fn cap_force_limits_large_input() {
+        let movement = Movement {
+            max_acceleration_force: Some(50.0),
+            ..default()
+        };
+
+        // Snapping to a huge goal velocity in a single 60hz step.
+        let force = Vec3::new(300.0, 0.0, -400.0) * 60.0;
+        let capped = movement.cap_force(force);
+        assert!((capped.length() - 50.0).abs() < 1e-3);
+        assert!(capped.normalize().dot(force.normalize()) > 1.0 - 1e-5);
+
+        // Forces below the cap, and uncapped movement, are left alone.
+        let small = Vec3::new(3.0, 0.0, 4.0);
+        assert_eq!(movement.cap_force(small), small);
+        assert_eq!(Movement::default().cap_force(force), force);
+    }
+
+    #[test]
+    fn target_speed_settles_at_sprint_speed() {
+        let mut movement = Movement {
+            max_speed: 6.0,
+            ..default()
+        };
+        assert_eq!(movement.target_speed(), 6.0);
+
+        for _ in 0..120 {
+            movement.update_target_speed(true, 1.0 / 60.0);
+        }
+        assert_eq!(movement.target_speed(), movement.sprint_speed);
+
+        // Back to walking follows `max_speed` again, including changes to it.
+        for _ in 0..120 {
+            movement.update_target_speed(false, 1.0 / 60.0);
+        }
+        assert_eq!(movement.target_speed, None);
+        movement.max_speed = 4.0;
+        assert_eq!(movement.target_speed(), 4.0);
+    }
+
+    #[test]
+    fn frozen_controller_keeps_jump_and_knockback_pending() {
+        let mut world = World::new();
+        world.insert_resource(RapierContext::default());
+        world.init_resource::<Events<JumpEvent>>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems((jump_force, knockback_force));
+
+        let mut controller = crate::ControllerBundle::default();
+        controller.controller.grounded = Grounded(true);
+        controller.input.jumping = true;
+        let entity = world
+            .spawn((controller, KnockbackBundle::default(), Freeze::default()))
+            .id();
+        let mut knockback = world.get_mut::<Knockback>(entity).unwrap();
+        knockback.apply(Vec3::X, 0.5);
+
+        // Neither the jump press nor the knockback are used up while frozen.
+        schedule.run(&mut world);
+        assert!(world.resource::<Events<JumpEvent>>().is_empty());
+        let jump = world.get::<Jump>(entity).unwrap();
+        assert!(!jump.pressed_last_frame);
+        assert_eq!(jump.cooldown_timer, 0.0);
+        assert_eq!(world.get::<Knockback>(entity).unwrap().impulse, Vec3::X);
+
+        // Unfreezing jumps and knocks back with the input that was held the whole time.
+        world.entity_mut(entity).remove::<Freeze>();
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<Events<JumpEvent>>().len(), 1);
+        let jump = world.get::<Jump>(entity).unwrap();
+        assert_eq!(jump.remaining_jumps, 0);
+        assert!(jump.on_cooldown());
+        assert_eq!(world.get::<Knockback>(entity).unwrap().impulse, Vec3::ZERO);
+        assert!(world.get::<KnockbackForce>(entity).unwrap().linear.x > 0.0);
+    }
+
+    #[test]
+    fn no_clip_controller_does_not_jump() {
+        let mut world = World::new();
+        world.insert_resource(RapierContext::default());
+        world.init_resource::<Events<JumpEvent>>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(jump_force);
+
+        let mut controller = crate::ControllerBundle::default();
+        controller.controller.grounded = Grounded(true);
+        controller.input.jumping = true;
+        let entity = world.spawn((controller, NoClip::default())).id();
 
-        jumping.pressed_last_frame = input.jumping;
+        schedule.run(&mut world);
+        assert!(world.resource::<Events<JumpEvent>>().is_empty());
+        assert!(!world.get::<Jump>(entity).unwrap().on_cooldown());
     }
 }
@@ -1,12 +1,34 @@
 use crate::controller::*;
-use crate::SpringStrength;
+use crate::{rotation_displacement, SpringStrength};
 
 /// Keeps the controller properly oriented in a floating state.
 #[derive(Component, Reflect)]
 #[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Float {
     /// How far to attempt to float away from the ground.
+    ///
+    /// This is the target distance, other systems (e.g. [`Crouch`]) can change it and
+    /// the controller will ease towards it based on `distance_smoothing`.
     pub distance: f32,
+    /// Point, in the controller's local space, that `distance` is measured from.
+    ///
+    /// Use this for colliders/models whose origin isn't where the float height should be
+    /// measured from, usually along with the same [`GroundCaster::cast_origin`].
+    pub anchor_offset: Vec3,
+    /// How quickly the float distance eases towards `distance` when it changes, higher is faster.
+    ///
+    /// `0.0` snaps to the new distance immediately.
+    pub distance_smoothing: f32,
+    /// The distance currently being floated at, this is eased towards `distance`.
+    ///
+    /// `None` until the controller has floated for a frame.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub current_distance: Option<f32>,
     /// While floating, the character can be floating at a different exact distance than [`float_distance`] depending on other forces acting on them.
     /// This field controls how much lower than [`float_distance`] they can be and still be considered grounded.
     ///
@@ -18,23 +40,72 @@ pub struct Float {
     /// This helps keep jumps more consistent when the ground cast length is longer than the float distance.
     pub max_offset: f32,
     /// How strongly to float away from the ground.
+    ///
+    /// This is only applied along the up vector, so for per-axis strengths only `y` is used.
+    /// The spring is solved over the whole physics step along with gravity, see [`Spring::implicit_force`].
     pub spring: Spring,
+    /// Don't apply any float force while moving up away from the ground faster than this.
+    ///
+    /// This keeps the float spring from interfering with the start of a jump. Since it's based
+    /// on velocity, landing re-enables the float immediately. `None` always applies the float force.
+    pub max_snap_velocity: Option<f32>,
+    /// Pull the controller back down to the float distance if it is further from the ground
+    /// than the float distance but within this distance, e.g. walking down stairs.
+    ///
+    /// This only applies while not moving up away from the ground. `None` never pulls the controller down.
+    pub snap_distance: Option<f32>,
+    /// Extra downward acceleration, per unit of horizontal speed, applied while grounded.
+    ///
+    /// This keeps fast controllers on the ground over bumps and the crests of slopes
+    /// instead of launching off of them. It is disabled while jumping. `0.0` disables it.
+    pub ground_stick_force: f32,
 }
 
 impl Default for Float {
     fn default() -> Self {
         Self {
             distance: 0.55,
+            anchor_offset: Vec3::ZERO,
+            distance_smoothing: 10.0,
+            current_distance: None,
             min_offset: -0.55,
             max_offset: 0.1,
             spring: Spring {
                 strength: SpringStrength::AngularFrequency(12.0),
                 damping: 0.8,
             },
+            max_snap_velocity: None,
+            snap_distance: None,
+            ground_stick_force: 0.0,
         }
     }
 }
 
+impl Float {
+    /// World space point the float distance is measured from, see `anchor_offset`.
+    pub fn anchor(&self, global: &GlobalTransform) -> Vec3 {
+        global.transform_point(self.anchor_offset)
+    }
+
+    /// The distance currently being floated at.
+    pub fn current_distance(&self) -> f32 {
+        self.current_distance.unwrap_or(self.distance)
+    }
+
+    /// Ease `current_distance` towards the target `distance`.
+    pub fn update_distance(&mut self, dt: f32) {
+        let current = self.current_distance();
+        let next = if self.distance_smoothing > 0.0 {
+            let t = 1.0 - (-self.distance_smoothing * dt).exp();
+            current + (self.distance - current) * t
+        } else {
+            self.distance
+        };
+
+        self.current_distance = Some(next);
+    }
+}
+
 /// Force applied to push the controller off the ground.
 #[derive(Component, Default, Reflect)]
 #[reflect(Component, Default)]
@@ -43,54 +114,147 @@ pub struct FloatForce {
     pub linear: Vec3,
 }
 
+/// State used to calculate [`FloatForce`].
+type FloatForceQuery = (
+    &'static GlobalTransform,
+    &'static mut FloatForce,
+    &'static mut Float,
+    &'static GroundCast,
+    &'static ViableGroundCast,
+    &'static ControllerVelocity,
+    &'static ControllerMass,
+    &'static Gravity,
+    &'static GravityForce,
+    &'static Grounded,
+    &'static Jump,
+    Option<&'static NoClip>,
+);
+
 /// Calculate "floating" force, as seen [here](https://www.youtube.com/watch?v=qdskE8PJy6Q)
-pub fn float_force(
-    mut query: Query<(
-        &GlobalTransform,
-        &mut FloatForce,
-        &Float,
-        &ViableGroundCast,
-        &ControllerVelocity,
-        &ControllerMass,
-        &Gravity,
-    )>,
-) {
-    for (global, mut force, float, viable_ground, velocity, mass, gravity) in &mut query {
-        force.linear = Vec3::ZERO;
+pub fn float_force(mut query: Query<FloatForceQuery>, ctx: Res<RapierContext>) {
+    let dt = ctx.integration_parameters.dt;
+    query.par_iter_mut().for_each_mut(
+        |(
+            global,
+            mut force,
+            mut float,
+            closest_ground,
+            viable_ground,
+            velocity,
+            mass,
+            gravity,
+            gravity_force,
+            grounded,
+            jump,
+            no_clip,
+        )| {
+            force.linear = Vec3::ZERO;
+            float.update_distance(dt);
 
-        let Some(ground) = viable_ground.current() else { continue };
+            if no_clip.is_some() {
+                return;
+            }
 
-        let up_vector = gravity.up_vector;
+            let Some(ground) = viable_ground.current() else { return };
 
-        let controller_point_velocity =
-            velocity.linear + velocity.angular.cross(Vec3::ZERO - mass.com);
-        let vel_align = up_vector.dot(controller_point_velocity);
-        let ground_vel_align = up_vector.dot(ground.point_velocity);
+            let up_vector = gravity.up_vector;
 
-        let relative_velocity = vel_align - ground_vel_align;
+            let controller_point_velocity =
+                velocity.linear + velocity.angular.cross(Vec3::ZERO - mass.com);
+            let vel_align = up_vector.dot(controller_point_velocity);
+            let ground_vel_align = up_vector.dot(ground.point_velocity);
 
-        let worldspace_diff =
-            global.translation().dot(gravity.up_vector) - ground.cast.point.dot(gravity.up_vector);
-        let displacement = float.distance - worldspace_diff;
-        //info!("displacement: {:.2?}", displacement);
+            let relative_velocity = vel_align - ground_vel_align;
+            if float
+                .max_snap_velocity
+                .is_some_and(|max| relative_velocity > max)
+            {
+                return;
+            }
 
-        if displacement > 0.0 {
-            let strength = displacement * float.spring.strength.get(Vec3::splat(mass.mass));
-            let damping = relative_velocity * float.spring.damp_coefficient(Vec3::splat(mass.mass));
-            force.linear += up_vector * (strength - damping);
-        }
-    }
+            let worldspace_diff = float.anchor(global).dot(gravity.up_vector)
+                - ground.cast.point.dot(gravity.up_vector);
+            let displacement = float.current_distance() - worldspace_diff;
+            //info!("displacement: {:.2?}", displacement);
+
+            // Pull back down to the ground if we've drifted a little too far from it,
+            // unless we're on ground too steep to stand on and should slide off of it instead.
+            let steep = closest_ground
+                .current()
+                .is_some_and(|ground| !ground.viable);
+            let snapping = float
+                .snap_distance
+                .is_some_and(|snap| worldspace_diff <= snap)
+                && relative_velocity <= 0.0
+                && ground.viable
+                && !steep;
+
+            if displacement > 0.0 || snapping {
+                // Solve the spring together with gravity, so we come to rest at the same height with any step.
+                let spring = float.spring.implicit_force(
+                    Vec3::splat(mass.mass),
+                    Vec3::splat(displacement),
+                    Vec3::splat(relative_velocity),
+                    Vec3::splat(gravity_force.linear.dot(up_vector)),
+                    dt,
+                );
+                force.linear += up_vector * spring.y;
+            }
+
+            let sticking = **grounded && !jump.jumping() && relative_velocity <= 0.0;
+            if float.ground_stick_force > 0.0 && sticking {
+                let horizontal = gravity.project(velocity.linear - ground.point_velocity);
+                force.linear -=
+                    up_vector * float.ground_stick_force * horizontal.length() * mass.mass;
+            }
+        },
+    );
 }
 
 /// How to keep the controller upright, as well as
 /// facing a specific direction.
 #[derive(Component, Reflect)]
 #[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Upright {
     /// How strongly to force the character upright/avoid overshooting. Alternatively, see [`LockedAxes`] to lock rotation entirely.
     pub spring: Spring,
+    /// How to rotate towards the target orientation.
+    pub mode: UprightMode,
     /// The direction to face towards, or `None` to not rotate to face any direction. Must be perpendicular to the up vector and normalized.
     pub forward_vector: Option<Vec3>,
+    /// Rotate `forward_vector` with the yaw of the ground we are standing on, e.g. a spinning platform.
+    ///
+    /// This stops as soon as the controller is no longer grounded.
+    pub rotate_with_ground: bool,
+    /// Drive `forward_vector` towards the direction the controller is moving, e.g. for third-person characters.
+    ///
+    /// Uses the horizontal velocity relative to the ground, facing is left unchanged
+    /// while moving slower than `face_movement_threshold`.
+    pub face_movement: bool,
+    /// Horizontal speed the controller has to be moving at before `face_movement` turns it.
+    pub face_movement_threshold: f32,
+    /// Max speed, in radians per second, that `face_movement` turns the controller.
+    ///
+    /// `None` faces the movement direction immediately.
+    pub turn_speed: Option<f32>,
+    /// How much to tilt with the slope of the ground while grounded, from `0.0` (stay
+    /// aligned with gravity) to `1.0` (fully aligned with the ground normal).
+    pub slope_alignment: f32,
+    /// Max angle, in radians, to tilt away from gravity's up when aligning with the slope.
+    ///
+    /// This keeps the controller from tipping over onto near-vertical surfaces.
+    pub max_slope_alignment_angle: f32,
+    /// How far to lean into the movement acceleration, in radians per unit of acceleration.
+    ///
+    /// This is purely cosmetic, leaning forward when speeding up and back when slowing down.
+    pub lean: f32,
+    /// Max angle, in radians, to lean.
+    pub max_lean_angle: f32,
 }
 
 impl Default for Upright {
@@ -100,11 +264,142 @@ impl Default for Upright {
                 strength: SpringStrength::AngularFrequency(25.0),
                 damping: 0.5,
             },
+            mode: UprightMode::Spring,
             forward_vector: None,
+            rotate_with_ground: false,
+            face_movement: false,
+            face_movement_threshold: 0.5,
+            turn_speed: Some(10.0),
+            slope_alignment: 0.0,
+            max_slope_alignment_angle: 45.0 * (std::f32::consts::PI / 180.0),
+            lean: 0.0,
+            max_lean_angle: 15.0 * (std::f32::consts::PI / 180.0),
+        }
+    }
+}
+
+/// How [`Upright`] rotates the controller towards the target orientation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UprightMode {
+    /// Use [`Upright::spring`].
+    #[default]
+    Spring,
+    /// Reach the target orientation in a single physics step.
+    Snap,
+    /// Rotate towards the target orientation at a fixed max speed, in radians per second.
+    MaxSpeed(f32),
+}
+
+impl Upright {
+    /// Rotate `forward` around `up_vector` towards `target`, limited by `turn_speed`.
+    pub fn turn_towards(&self, forward: Vec3, target: Vec3, up_vector: Vec3, dt: f32) -> Vec3 {
+        let Some(turn_speed) = self.turn_speed else { return target };
+
+        let sin = forward.cross(target).dot(up_vector);
+        let angle = sin.atan2(forward.dot(target));
+        let max_angle = turn_speed * dt;
+        if angle.abs() <= max_angle {
+            return target;
+        }
+
+        Quat::from_axis_angle(up_vector, max_angle.copysign(angle)) * forward
+    }
+
+    /// Up direction to align to when standing on ground with `normal`, see `slope_alignment`.
+    pub fn aligned_up(&self, up_vector: Vec3, normal: Vec3) -> Vec3 {
+        let Some(axis) = up_vector.cross(normal).try_normalize() else { return up_vector };
+
+        let angle = up_vector
+            .angle_between(normal)
+            .min(self.max_slope_alignment_angle.max(0.0));
+        Quat::from_axis_angle(axis, angle * self.slope_alignment.clamp(0.0, 1.0)) * up_vector
+    }
+
+    /// Tilt `up_vector` towards the horizontal part of `acceleration`, see `lean`.
+    pub fn lean_up(&self, up_vector: Vec3, acceleration: Vec3) -> Vec3 {
+        let acceleration = acceleration - up_vector * acceleration.dot(up_vector);
+        let Some(axis) = up_vector.cross(acceleration).try_normalize() else { return up_vector };
+
+        let angle = (acceleration.length() * self.lean).min(self.max_lean_angle.max(0.0));
+        Quat::from_axis_angle(axis, angle) * up_vector
+    }
+}
+
+/// Camera relative movement for third person controllers, e.g. from a gamepad stick.
+///
+/// Set `stick` every frame and [`camera_relative_input`] fills in [`ControllerInput::movement`]
+/// and turns [`Upright::forward_vector`] towards it, limited by [`Upright::turn_speed`].
+/// Don't combine this with [`Upright::face_movement`].
+///
+/// This isn't part of [`Controller`], add it to controllers that should be driven relative to a camera.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct CameraRelativeInput {
+    /// Movement input, `y` is forward and `x` is right relative to the camera. The length is clamped to `1.0`.
+    pub stick: Vec2,
+    /// Camera to move relative to, if this is `None` forward is `Vec3::NEG_Z`.
+    pub camera: Option<Entity>,
+    /// Turn the controller to face the direction it is being moved in.
+    pub face_input: bool,
+    /// Stick length needed before the controller turns to face it.
+    pub face_threshold: f32,
+}
+
+impl Default for CameraRelativeInput {
+    fn default() -> Self {
+        Self {
+            stick: Vec2::ZERO,
+            camera: None,
+            face_input: true,
+            face_threshold: 0.1,
         }
     }
 }
 
+impl CameraRelativeInput {
+    /// World space direction of `stick` relative to `camera_rotation`, see [`camera_relative_movement`].
+    pub fn direction(&self, camera_rotation: Quat, up_vector: Vec3) -> Vec3 {
+        camera_relative_movement(self.stick, camera_rotation, up_vector)
+    }
+}
+
+/// Turn [`CameraRelativeInput`] into movement input and a facing direction.
+pub fn camera_relative_input(
+    mut query: Query<(
+        &CameraRelativeInput,
+        &mut ControllerInput,
+        &mut Upright,
+        &GlobalTransform,
+        &Gravity,
+    )>,
+    cameras: Query<&GlobalTransform>,
+    ctx: Res<RapierContext>,
+) {
+    let dt = ctx.integration_parameters.dt;
+    for (relative, mut input, mut upright, tf, gravity) in &mut query {
+        let camera_rotation = relative
+            .camera
+            .and_then(|camera| cameras.get(camera).ok())
+            .map_or(Quat::IDENTITY, |camera| {
+                camera.to_scale_rotation_translation().1
+            });
+
+        let direction = relative.direction(camera_rotation, gravity.up_vector);
+        input.movement = direction;
+
+        if !relative.face_input || direction.length() < relative.face_threshold {
+            continue;
+        }
+
+        let target = direction.normalize();
+        let forward = upright
+            .forward_vector
+            .unwrap_or_else(|| gravity.project(tf.back()).normalize_or_zero());
+        upright.forward_vector = Some(upright.turn_towards(forward, target, gravity.up_vector, dt));
+    }
+}
+
 /// Forces applied to keep the controller upright and optionally facing a direction.
 #[derive(Component, Default, Reflect)]
 #[reflect(Component, Default)]
@@ -113,38 +408,90 @@ pub struct UprightForce {
     pub angular: Vec3,
 }
 
+/// State used to calculate [`UprightForce`].
+type UprightForceQuery = (
+    &'static mut UprightForce,
+    &'static mut Upright,
+    &'static GlobalTransform,
+    &'static Gravity,
+    &'static ControllerMass,
+    &'static ControllerVelocity,
+    &'static ViableGroundCast,
+    &'static Grounded,
+    &'static MovementForce,
+);
+
 /// Make sure the controller stays upright/does not tilt or fall over on its side.
-pub fn upright_force(
-    mut query: Query<(
-        &mut UprightForce,
-        &Upright,
-        &GlobalTransform,
-        &Gravity,
-        &ControllerMass,
-        &ControllerVelocity,
-        &ViableGroundCast,
-    )>,
-) {
-    for (mut impulse, upright, tf, gravity, mass, velocity, viable_ground) in &mut query {
+pub fn upright_force(mut query: Query<UprightForceQuery>, ctx: Res<RapierContext>) {
+    let dt = ctx.integration_parameters.dt;
+    for (
+        mut impulse,
+        mut upright,
+        tf,
+        gravity,
+        mass,
+        velocity,
+        viable_ground,
+        grounded,
+        movement_force,
+    ) in &mut query
+    {
+        if upright.rotate_with_ground && **grounded {
+            if let (Some(ground), Some(forward)) = (viable_ground.current(), upright.forward_vector)
+            {
+                let yaw = ground.angular_velocity.dot(gravity.up_vector) * dt;
+                upright.forward_vector =
+                    Some(Quat::from_axis_angle(gravity.up_vector, yaw) * forward);
+            }
+        }
+
+        if upright.face_movement {
+            let ground_velocity = match viable_ground.current() {
+                Some(ground) if **grounded => ground.point_velocity,
+                _ => Vec3::ZERO,
+            };
+            let horizontal = gravity.project(velocity.linear - ground_velocity);
+
+            if horizontal.length() > upright.face_movement_threshold {
+                let target = horizontal.normalize();
+                let forward = upright
+                    .forward_vector
+                    .and_then(|forward| gravity.project(forward).try_normalize())
+                    .or_else(|| gravity.project(tf.back()).try_normalize())
+                    .unwrap_or(target);
+                upright.forward_vector =
+                    Some(upright.turn_towards(forward, target, gravity.up_vector, dt));
+            }
+        }
+
+        let target_up = match viable_ground.current() {
+            Some(ground) if **grounded && upright.slope_alignment > 0.0 => {
+                upright.aligned_up(gravity.up_vector, ground.cast.normal)
+            }
+            _ => gravity.up_vector,
+        };
+
+        let target_up = if upright.lean != 0.0 && mass.mass > 0.0 {
+            upright.lean_up(target_up, movement_force.linear / mass.mass)
+        } else {
+            target_up
+        };
+
         impulse.angular = {
             let desired_axis = if let Some(forward) = upright.forward_vector {
-                let right = gravity.up_vector.cross(forward).normalize();
+                let forward = (forward - target_up * forward.dot(target_up))
+                    .try_normalize()
+                    .unwrap_or(forward);
+                let right = target_up.cross(forward).normalize();
                 let up = forward.cross(right);
                 let target_rot = Quat::from_mat3(&Mat3::from_cols(right, up, forward));
                 let current = tf.to_scale_rotation_translation().1;
-                let rot = target_rot * current.inverse();
-                let (axis, mut angle) = rot.to_axis_angle();
-                if angle > std::f32::consts::PI {
-                    angle -= 2.0 * std::f32::consts::PI;
-                }
-                axis * angle
+                rotation_displacement(current, target_rot)
             } else {
                 let current = tf.up();
-                current.cross(gravity.up_vector)
+                current.cross(target_up)
             };
 
-            let damping = upright.spring.damp_coefficient(mass.inertia);
-
             let ground_rot = if let Some(ground) = viable_ground.last() {
                 ground.angular_velocity.project_onto(gravity.up_vector)
             } else {
@@ -153,10 +500,84 @@ pub fn upright_force(
 
             let local_velocity = velocity.angular - ground_rot;
 
-            let spring = (desired_axis * upright.spring.strength.get(mass.inertia))
-                - (local_velocity * damping);
-            //spring.clamp_length_max(upright.spring.strength)
-            spring
+            match upright.mode {
+                UprightMode::Spring => upright.spring.implicit_force(
+                    mass.inertia,
+                    desired_axis,
+                    local_velocity,
+                    Vec3::ZERO,
+                    dt,
+                ),
+                UprightMode::Snap => {
+                    let goal_velocity = desired_axis / dt;
+                    (goal_velocity - local_velocity) * mass.inertia / dt
+                }
+                UprightMode::MaxSpeed(max_speed) => {
+                    let goal_velocity = (desired_axis / dt).clamp_length_max(max_speed);
+                    (goal_velocity - local_velocity) * mass.inertia / dt
+                }
+            }
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Height a default float settles at over flat ground, with the forces calculated once per
+    /// physics step of `dt` and the step split into `substeps` like rapier does.
+    fn settled_height(dt: f32, substeps: u32) -> f32 {
+        let float = Float::default();
+        let gravity = Gravity::default();
+        let mass = 80.0;
+        let gravity_force = gravity.acceleration * mass;
+
+        let (mut height, mut velocity) = (float.distance + 0.3, 0.0);
+        for _ in 0..(3.0 / dt) as u32 {
+            let displacement = float.distance - height;
+            let spring = if displacement > 0.0 {
+                float
+                    .spring
+                    .implicit_force(
+                        Vec3::splat(mass),
+                        Vec3::splat(displacement),
+                        Vec3::splat(velocity),
+                        Vec3::splat(gravity_force),
+                        dt,
+                    )
+                    .y
+            } else {
+                0.0
+            };
+
+            let substep = dt / substeps as f32;
+            for _ in 0..substeps {
+                velocity += (spring + gravity_force) / mass * substep;
+                height += velocity * substep;
+            }
+        }
+
+        height
+    }
+
+    #[test]
+    fn float_height_independent_of_substeps() {
+        let coarse = settled_height(1.0 / 60.0, 4);
+        let fine = settled_height(1.0 / 60.0, 32);
+        assert!((coarse - fine).abs() < 1e-3, "{coarse} vs {fine}");
+    }
+
+    #[test]
+    fn float_height_independent_of_step_length() {
+        // Resting where the spring holds up the controller's weight, `kx = mg`.
+        let float = Float::default();
+        let stiffness = float.spring.strength.get(Vec3::ONE).y;
+        let expected = float.distance + Gravity::default().acceleration / stiffness;
+
+        for dt in [1.0 / 30.0, 1.0 / 60.0, 1.0 / 144.0] {
+            let height = settled_height(dt, 4);
+            assert!((height - expected).abs() < 1e-3, "{height} at {dt}");
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::controller::*;
+
+/// Sliding, started by crouching while moving fast on the ground.
+///
+/// While sliding friction is mostly taken away, movement input only steers and slopes speed the
+/// controller up, so momentum carries it. The slide ends when it slows down below `min_speed`,
+/// crouching is released or the controller leaves the ground.
+///
+/// This isn't part of [`Controller`], add it along with a [`Crouch`] to controllers that should slide.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct Slide {
+    /// Horizontal speed, relative to the ground, needed to start sliding when crouching.
+    pub entry_speed: f32,
+    /// Horizontal speed, relative to the ground, below which the slide ends.
+    pub min_speed: f32,
+    /// Multiplier for the ground friction while sliding.
+    pub friction: f32,
+    /// Multiplier for [`Movement::acceleration`] while sliding, how much the controller can steer.
+    pub steering: f32,
+    /// Multiplier for gravity pulling the controller down slopes while sliding.
+    pub slope_acceleration: f32,
+    /// Is the controller currently sliding.
+    pub sliding: bool,
+}
+
+impl Default for Slide {
+    fn default() -> Self {
+        Self {
+            entry_speed: 6.0,
+            min_speed: 2.0,
+            friction: 0.05,
+            steering: 0.2,
+            slope_acceleration: 1.0,
+            sliding: false,
+        }
+    }
+}
+
+/// Sent when a controller starts or stops sliding.
+#[derive(Event, Debug, Clone)]
+pub struct SlideEvent {
+    /// Controller that started/stopped sliding.
+    pub entity: Entity,
+    /// Did the controller start sliding, otherwise it stopped.
+    pub sliding: bool,
+    /// Horizontal speed of the controller relative to the ground.
+    pub speed: f32,
+}
+
+/// Start and stop slides, see [`Slide`].
+pub fn slide(
+    mut query: Query<(
+        Entity,
+        &mut Slide,
+        &ControllerInput,
+        &Gravity,
+        &ControllerVelocity,
+        &GroundContact,
+        &Grounded,
+    )>,
+    mut events: EventWriter<SlideEvent>,
+) {
+    for (entity, mut slide, input, gravity, velocity, contact, grounded) in &mut query {
+        let speed = gravity
+            .project(velocity.linear - contact.surface_velocity)
+            .length();
+
+        let sliding = if slide.sliding {
+            input.crouching && **grounded && speed >= slide.min_speed
+        } else {
+            input.crouching && **grounded && speed >= slide.entry_speed
+        };
+
+        if sliding != slide.sliding {
+            slide.sliding = sliding;
+            events.send(SlideEvent {
+                entity,
+                sliding,
+                speed,
+            });
+        }
+    }
+}
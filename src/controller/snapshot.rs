@@ -0,0 +1,299 @@
+use crate::controller::*;
+
+/// The transient state of a controller (velocity, groundedness, jump counters and timers), without
+/// any of its tuning, for save games and networking rollback.
+///
+/// [`Dash`] and [`Knockback`] state is included when the controller has them.
+///
+/// ```ignore
+/// let snapshot = ControllerSnapshot::extract(world, entity).unwrap();
+/// // ...
+/// snapshot.apply(world, entity);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControllerSnapshot {
+    /// See [`ControllerVelocity::linear`].
+    pub linear_velocity: Vec3,
+    /// See [`ControllerVelocity::angular`].
+    pub angular_velocity: Vec3,
+    /// See [`Grounded`].
+    pub grounded: bool,
+
+    /// See [`Jump::remaining_jumps`].
+    pub remaining_jumps: u32,
+    /// See [`Jump::jump_timer`].
+    pub jump_timer: f32,
+    /// See [`Jump::cooldown_timer`].
+    pub jump_cooldown_timer: f32,
+    /// See [`Jump::buffer_timer`].
+    pub jump_buffer_timer: f32,
+    /// See [`Jump::coyote_timer`].
+    pub coyote_timer: f32,
+    /// See [`Jump::pressed_last_frame`].
+    pub jump_pressed_last_frame: bool,
+    /// See [`Jump::apex_origin`].
+    pub jump_apex_origin: Option<Vec3>,
+
+    /// See [`GroundCaster::skip_ground_check_timer`].
+    pub skip_ground_check_timer: f32,
+    /// See [`GroundCaster::airborne_timer`].
+    pub airborne_timer: f32,
+    /// See [`GroundCaster::groundedness_timer`].
+    pub groundedness_timer: f32,
+    /// See [`GroundCaster::left_ground_sent`].
+    pub left_ground_sent: bool,
+
+    /// See [`Movement::target_speed`].
+    pub target_speed: Option<f32>,
+    /// See [`Movement::moving`].
+    pub moving: bool,
+    /// See [`Float::current_distance`].
+    pub float_distance: Option<f32>,
+
+    /// State of the controller's [`Dash`], if it has one.
+    pub dash: Option<DashSnapshot>,
+    /// State of the controller's [`Knockback`], if it has one.
+    pub knockback: Option<KnockbackSnapshot>,
+}
+
+/// The transient state of a [`Dash`], see [`ControllerSnapshot::dash`].
+#[derive(Debug, Default, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DashSnapshot {
+    /// See [`Dash::dash_timer`].
+    pub dash_timer: f32,
+    /// See [`Dash::cooldown_timer`].
+    pub cooldown_timer: f32,
+    /// See [`Dash::direction`].
+    pub direction: Vec3,
+    /// See [`Dash::pressed_last_frame`].
+    pub pressed_last_frame: bool,
+}
+
+/// The transient state of a [`Knockback`], see [`ControllerSnapshot::knockback`].
+#[derive(Debug, Default, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KnockbackSnapshot {
+    /// See [`Knockback::control_lockout`].
+    pub control_lockout: f32,
+    /// See [`Knockback::lockout_timer`].
+    pub lockout_timer: f32,
+}
+
+impl ControllerSnapshot {
+    /// Snapshot the state of the controller `entity`, `None` if it isn't a full [`Controller`].
+    pub fn extract(world: &World, entity: Entity) -> Option<Self> {
+        let entity = world.get_entity(entity)?;
+        let velocity = entity.get::<ControllerVelocity>()?;
+        let grounded = entity.get::<Grounded>()?;
+        let jump = entity.get::<Jump>()?;
+        let caster = entity.get::<GroundCaster>()?;
+        let movement = entity.get::<Movement>()?;
+        let float = entity.get::<Float>()?;
+        let dash = entity.get::<Dash>();
+        let knockback = entity.get::<Knockback>();
+
+        Some(Self {
+            linear_velocity: velocity.linear,
+            angular_velocity: velocity.angular,
+            grounded: grounded.0,
+
+            remaining_jumps: jump.remaining_jumps,
+            jump_timer: jump.jump_timer,
+            jump_cooldown_timer: jump.cooldown_timer,
+            jump_buffer_timer: jump.buffer_timer,
+            coyote_timer: jump.coyote_timer,
+            jump_pressed_last_frame: jump.pressed_last_frame,
+            jump_apex_origin: jump.apex_origin,
+
+            skip_ground_check_timer: caster.skip_ground_check_timer,
+            airborne_timer: caster.airborne_timer,
+            groundedness_timer: caster.groundedness_timer,
+            left_ground_sent: caster.left_ground_sent,
+
+            target_speed: movement.target_speed,
+            moving: movement.moving,
+            float_distance: float.current_distance,
+
+            dash: dash.map(|dash| DashSnapshot {
+                dash_timer: dash.dash_timer,
+                cooldown_timer: dash.cooldown_timer,
+                direction: dash.direction,
+                pressed_last_frame: dash.pressed_last_frame,
+            }),
+            knockback: knockback.map(|knockback| KnockbackSnapshot {
+                control_lockout: knockback.control_lockout,
+                lockout_timer: knockback.lockout_timer,
+            }),
+        })
+    }
+
+    /// Restore this snapshot onto the controller `entity`, returns false if it isn't a full [`Controller`].
+    ///
+    /// This also sets the rapier [`Velocity`] so the restored velocity isn't immediately overwritten.
+    pub fn apply(&self, world: &mut World, entity: Entity) -> bool {
+        let Some(mut entity) = world.get_entity_mut(entity) else { return false };
+        let has_controller = entity.contains::<ControllerVelocity>()
+            && entity.contains::<Grounded>()
+            && entity.contains::<Jump>()
+            && entity.contains::<GroundCaster>()
+            && entity.contains::<Movement>()
+            && entity.contains::<Float>();
+        if !has_controller {
+            return false;
+        }
+
+        if let Some(mut velocity) = entity.get_mut::<ControllerVelocity>() {
+            velocity.linear = self.linear_velocity;
+            velocity.angular = self.angular_velocity;
+        }
+
+        if let Some(mut velocity) = entity.get_mut::<Velocity>() {
+            velocity.linvel = self.linear_velocity;
+            velocity.angvel = self.angular_velocity;
+        }
+
+        if let Some(mut grounded) = entity.get_mut::<Grounded>() {
+            grounded.0 = self.grounded;
+        }
+
+        if let Some(mut jump) = entity.get_mut::<Jump>() {
+            jump.remaining_jumps = self.remaining_jumps;
+            jump.jump_timer = self.jump_timer;
+            jump.cooldown_timer = self.jump_cooldown_timer;
+            jump.buffer_timer = self.jump_buffer_timer;
+            jump.coyote_timer = self.coyote_timer;
+            jump.pressed_last_frame = self.jump_pressed_last_frame;
+            jump.apex_origin = self.jump_apex_origin;
+        }
+
+        if let Some(mut caster) = entity.get_mut::<GroundCaster>() {
+            caster.skip_ground_check_timer = self.skip_ground_check_timer;
+            caster.airborne_timer = self.airborne_timer;
+            caster.groundedness_timer = self.groundedness_timer;
+            caster.left_ground_sent = self.left_ground_sent;
+            // Force a fresh ground cast from the restored position.
+            caster.last_cast = None;
+        }
+
+        if let Some(mut movement) = entity.get_mut::<Movement>() {
+            movement.target_speed = self.target_speed;
+            movement.moving = self.moving;
+            movement.last_carry_velocity = None;
+        }
+
+        if let Some(mut float) = entity.get_mut::<Float>() {
+            float.current_distance = self.float_distance;
+        }
+
+        if let (Some(snapshot), Some(mut dash)) = (&self.dash, entity.get_mut::<Dash>()) {
+            dash.dash_timer = snapshot.dash_timer;
+            dash.cooldown_timer = snapshot.cooldown_timer;
+            dash.direction = snapshot.direction;
+            dash.pressed_last_frame = snapshot.pressed_last_frame;
+        }
+
+        if let (Some(snapshot), Some(mut knockback)) =
+            (&self.knockback, entity.get_mut::<Knockback>())
+        {
+            knockback.control_lockout = snapshot.control_lockout;
+            knockback.lockout_timer = snapshot.lockout_timer;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_controller(world: &mut World) -> Entity {
+        world
+            .spawn((
+                ControllerVelocity::default(),
+                Velocity::default(),
+                Grounded::default(),
+                Jump::default(),
+                GroundCaster::default(),
+                Movement::default(),
+                Float::default(),
+                Dash::default(),
+                Knockback::default(),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let mut world = World::new();
+        let source = spawn_controller(&mut world);
+        let snapshot = ControllerSnapshot {
+            linear_velocity: Vec3::new(1.0, 2.0, 3.0),
+            angular_velocity: Vec3::Y,
+            grounded: true,
+            remaining_jumps: 0,
+            jump_timer: 0.05,
+            jump_cooldown_timer: 0.2,
+            jump_buffer_timer: 0.1,
+            coyote_timer: 0.15,
+            jump_pressed_last_frame: true,
+            jump_apex_origin: Some(Vec3::new(0.0, 1.5, 0.0)),
+            skip_ground_check_timer: 0.3,
+            airborne_timer: 0.4,
+            groundedness_timer: 0.02,
+            left_ground_sent: true,
+            target_speed: Some(7.5),
+            moving: true,
+            float_distance: Some(0.8),
+            dash: Some(DashSnapshot {
+                dash_timer: 0.1,
+                cooldown_timer: 0.35,
+                direction: Vec3::NEG_Z,
+                pressed_last_frame: true,
+            }),
+            knockback: Some(KnockbackSnapshot {
+                control_lockout: 0.5,
+                lockout_timer: 0.25,
+            }),
+        };
+        assert!(snapshot.apply(&mut world, source));
+        let extracted = ControllerSnapshot::extract(&world, source).unwrap();
+        assert_eq!(extracted, snapshot);
+
+        // Restoring onto another controller gives the same state.
+        let target = spawn_controller(&mut world);
+        assert!(extracted.apply(&mut world, target));
+        let restored = ControllerSnapshot::extract(&world, target).unwrap();
+        assert_eq!(restored, snapshot);
+
+        let velocity = world.get::<Velocity>(target).unwrap();
+        assert_eq!(velocity.linvel, snapshot.linear_velocity);
+        assert_eq!(velocity.angvel, snapshot.angular_velocity);
+    }
+
+    #[test]
+    fn snapshot_skips_missing_sections() {
+        let mut world = World::new();
+        let entity = spawn_controller(&mut world);
+        world.entity_mut(entity).remove::<(Dash, Knockback)>();
+
+        let snapshot = ControllerSnapshot::extract(&world, entity).unwrap();
+        assert_eq!(snapshot.dash, None);
+        assert_eq!(snapshot.knockback, None);
+        assert!(snapshot.apply(&mut world, entity));
+    }
+
+    #[test]
+    fn snapshot_needs_full_controller() {
+        let mut world = World::new();
+        let partial = world
+            .spawn((ControllerVelocity::default(), Jump::default()))
+            .id();
+
+        assert_eq!(ControllerSnapshot::extract(&world, partial), None);
+        assert!(!ControllerSnapshot::default().apply(&mut world, partial));
+        assert_eq!(world.get::<Jump>(partial).unwrap().remaining_jumps, 1);
+    }
+}
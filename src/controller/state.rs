@@ -0,0 +1,69 @@
+use crate::controller::*;
+
+/// What the controller is currently doing, derived every frame from the rest of the controller.
+///
+/// When multiple states apply, the first in this order is used:
+/// [`Jumping`](ControllerStateKind::Jumping), [`Sliding`](ControllerStateKind::Sliding),
+/// [`Grounded`](ControllerStateKind::Grounded), [`Floating`](ControllerStateKind::Floating),
+/// [`Airborne`](ControllerStateKind::Airborne).
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+#[reflect(Component, Default)]
+pub struct ControllerState {
+    /// State this frame.
+    pub current: ControllerStateKind,
+    /// State last frame, compare with `current` to detect transitions.
+    pub previous: ControllerStateKind,
+}
+
+impl ControllerState {
+    /// Did the state change this frame.
+    pub fn changed(&self) -> bool {
+        self.current != self.previous
+    }
+}
+
+/// States a controller can be in, see [`ControllerState`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum ControllerStateKind {
+    /// Standing on viable ground, see [`Grounded`].
+    Grounded,
+    /// Not near any ground.
+    #[default]
+    Airborne,
+    /// On ground too steep to stand still on, see [`GroundCaster::unstable_ground_angle`].
+    Sliding,
+    /// In the middle of a jump, see [`Jump::jumping`].
+    Jumping,
+    /// Above viable ground, but too far from it to be grounded.
+    Floating,
+}
+
+/// Derive the [`ControllerState`] from the rest of the controller.
+pub fn controller_state(
+    mut query: Query<(
+        &mut ControllerState,
+        &Jump,
+        &Grounded,
+        &GroundCast,
+        &ViableGroundCast,
+    )>,
+) {
+    for (mut state, jump, grounded, ground, viable_ground) in &mut query {
+        let sliding = ground.current().is_some_and(|ground| !ground.stable);
+
+        let current = if jump.jumping() {
+            ControllerStateKind::Jumping
+        } else if sliding {
+            ControllerStateKind::Sliding
+        } else if **grounded {
+            ControllerStateKind::Grounded
+        } else if viable_ground.current().is_some() {
+            ControllerStateKind::Floating
+        } else {
+            ControllerStateKind::Airborne
+        };
+
+        state.previous = state.current;
+        state.current = current;
+    }
+}
@@ -0,0 +1,119 @@
+use crate::controller::*;
+use crate::RapierBackend;
+
+/// Stepping up onto small ledges (stairs, curbs) instead of being stopped by them.
+///
+/// This isn't part of [`Controller`], add it to controllers that should be able to step up.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct StepOffset {
+    /// Tallest ledge, relative to the current ground, that the controller can step onto.
+    pub max_step_height: f32,
+    /// How far ahead of the controller to look for a ledge to step onto.
+    pub max_step_forward: f32,
+}
+
+impl Default for StepOffset {
+    fn default() -> Self {
+        Self {
+            max_step_height: 0.3,
+            max_step_forward: 0.2,
+        }
+    }
+}
+
+/// Grounded controllers that might need to step up onto a ledge.
+type StepOffsetQuery = (
+    Entity,
+    &'static GlobalTransform,
+    &'static Gravity,
+    &'static GroundCaster,
+    &'static StepOffset,
+    &'static ControllerInput,
+    &'static mut ViableGroundCast,
+    &'static mut Grounded,
+);
+
+/// Treat the top of a small ledge in front of the controller as the ground,
+/// so [`Float`] lifts the controller onto it.
+pub fn step_offset(
+    mut query: Query<StepOffsetQuery>,
+
+    rapier: RapierBackend,
+    #[cfg(feature = "debug_lines")] mut gizmos: Gizmos,
+    #[cfg(feature = "debug_lines")] debug: Res<crate::WanderlustDebugConfig>,
+) {
+    #[cfg(feature = "debug_lines")]
+    let mut gizmos = debug.casts.then_some(&mut gizmos);
+    #[cfg(not(feature = "debug_lines"))]
+    let mut gizmos: Option<&mut Gizmos> = None;
+
+    let ctx = &*rapier.ctx;
+    for (entity, tf, gravity, caster, step, input, mut viable_ground, mut grounded) in &mut query {
+        if !**grounded {
+            continue;
+        }
+
+        let Some(current_ground) = viable_ground.current().copied() else { continue };
+
+        let up_vector = caster.up_vector(gravity);
+        let direction =
+            (input.movement - up_vector * input.movement.dot(up_vector)).normalize_or_zero();
+        if direction == Vec3::ZERO {
+            continue;
+        }
+
+        let Ok(caster_collider) = rapier.colliders.get(entity) else { continue };
+        let shape = caster.cast_collider.as_ref().unwrap_or(caster_collider);
+
+        let predicate =
+            |collider| collider != entity && !caster.exclude_from_ground.contains(&collider);
+        let filter = QueryFilter::new()
+            .exclude_sensors()
+            .groups(caster.ground_groups)
+            .predicate(&predicate);
+
+        let rotation = tf.to_scale_rotation_translation().1;
+        let mut params = GroundCastParams {
+            position: tf.transform_point(caster.cast_origin),
+            rotation,
+            direction,
+            shape,
+            max_toi: step.max_step_forward,
+            filter,
+            kind: GroundCastKind::Shape,
+        };
+
+        // We only need to step if something is blocking us, and it isn't a slope we can walk up.
+        let Some((_, blocking)) = params.cast_shape(ctx, gizmos.as_deref_mut()) else { continue };
+        if blocking.viable(up_vector, caster.max_ground_angle) {
+            continue;
+        }
+
+        // Make sure it isn't a wall taller than we can step.
+        params.position += up_vector * step.max_step_height;
+        if let Some((_, raised)) = params.cast_shape(ctx, gizmos.as_deref_mut()) {
+            if raised.toi <= blocking.toi + FUDGE {
+                continue;
+            }
+        }
+
+        // Find the top of the step.
+        params.position += direction * (blocking.toi + FUDGE).min(step.max_step_forward);
+        params.direction = -up_vector;
+        params.max_toi = step.max_step_height + caster.cast_length;
+        let Some((step_entity, step_cast)) = params.cast_shape(ctx, gizmos.as_deref_mut()) else { continue };
+
+        let step_height = (step_cast.point - current_ground.cast.point).dot(up_vector);
+        if step_height <= 0.0
+            || step_height > step.max_step_height
+            || !step_cast.viable(up_vector, caster.max_ground_angle)
+        {
+            continue;
+        }
+
+        let body = RapierBackend::point_velocity(&rapier, step_entity, step_cast.point);
+        viable_ground.update(Some(Ground::from_cast(step_cast, up_vector, caster, body)));
+        grounded.0 = true;
+    }
+}
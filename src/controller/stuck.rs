@@ -0,0 +1,100 @@
+use crate::controller::*;
+
+/// Detecting when the controller is trying to move but isn't getting anywhere, e.g. wedged in geometry.
+///
+/// Over every `window` the horizontal distance moved is compared to the distance
+/// [`ControllerInput::movement`] asked for at [`Movement::target_speed()`].
+///
+/// This isn't part of [`Controller`], add it to controllers that need to know when they're stuck.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct StuckDetection {
+    /// How long, in seconds, to compare the requested and actual movement over.
+    pub window: f32,
+    /// Fraction of the requested distance the controller has to move over a `window` to not be stuck.
+    pub threshold: f32,
+    /// Is the controller currently stuck.
+    pub stuck: bool,
+    /// Distance requested by the movement input so far this window.
+    pub requested: f32,
+    /// Position the current window started at.
+    pub start: Option<Vec3>,
+    /// Timer for tracking `window`.
+    pub timer: f32,
+}
+
+impl Default for StuckDetection {
+    fn default() -> Self {
+        Self {
+            window: 0.5,
+            threshold: 0.1,
+            stuck: false,
+            requested: 0.0,
+            start: None,
+            timer: 0.0,
+        }
+    }
+}
+
+/// Sent when a controller becomes stuck or gets unstuck, see [`StuckDetection`].
+#[derive(Event, Debug, Clone)]
+pub struct StuckEvent {
+    /// Controller that got stuck/unstuck.
+    pub entity: Entity,
+    /// Is the controller now stuck.
+    pub stuck: bool,
+}
+
+/// Compare requested and actual movement to find out if controllers are stuck.
+pub fn stuck_detection(
+    mut query: Query<(
+        Entity,
+        &mut StuckDetection,
+        &GlobalTransform,
+        &ControllerInput,
+        &Movement,
+        &Gravity,
+    )>,
+    ctx: Res<RapierContext>,
+    mut events: EventWriter<StuckEvent>,
+) {
+    let dt = ctx.integration_parameters.dt;
+    for (entity, mut detection, tf, input, movement, gravity) in &mut query {
+        let position = tf.translation();
+        let requested_speed =
+            movement.apply_deadzone(input.movement).length() * movement.target_speed();
+
+        // Not trying to move, so we can't be stuck.
+        if requested_speed <= 0.0 {
+            detection.requested = 0.0;
+            detection.start = None;
+            detection.timer = 0.0;
+            if detection.stuck {
+                detection.stuck = false;
+                events.send(StuckEvent {
+                    entity,
+                    stuck: false,
+                });
+            }
+            continue;
+        }
+
+        let start = *detection.start.get_or_insert(position);
+        detection.requested += requested_speed * dt;
+        detection.timer += dt;
+        if detection.timer < detection.window {
+            continue;
+        }
+
+        let moved = gravity.project(position - start).length();
+        let stuck = moved < detection.requested * detection.threshold;
+        if stuck != detection.stuck {
+            detection.stuck = stuck;
+            events.send(StuckEvent { entity, stuck });
+        }
+
+        detection.requested = 0.0;
+        detection.start = Some(position);
+        detection.timer = 0.0;
+    }
+}
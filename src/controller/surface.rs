@@ -0,0 +1,37 @@
+use crate::controller::*;
+
+/// Movement properties of a surface, e.g. slippery ice or slow mud.
+///
+/// Add this to ground entities (the rigid body if the collider has a parent) to change how
+/// controllers standing on them move, see [`movement_force`]. Ground without this uses the defaults.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct SurfaceProperties {
+    /// Scales the friction and braking the controller gets from the ground, lower is more slippery.
+    pub friction_scale: f32,
+    /// Scales [`Movement::acceleration`] while on this surface.
+    pub accel_scale: f32,
+    /// Scales the speed the controller can move at on this surface.
+    pub max_speed_scale: f32,
+}
+
+impl Default for SurfaceProperties {
+    fn default() -> Self {
+        Self {
+            friction_scale: 1.0,
+            accel_scale: 1.0,
+            max_speed_scale: 1.0,
+        }
+    }
+}
+
+impl SurfaceProperties {
+    /// Slippery surface, barely any friction or grip.
+    pub fn ice() -> Self {
+        Self {
+            friction_scale: 0.05,
+            accel_scale: 0.2,
+            max_speed_scale: 1.0,
+        }
+    }
+}
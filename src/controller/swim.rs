@@ -0,0 +1,141 @@
+use crate::controller::*;
+use crate::{SpringStrength, Strength};
+
+/// A collider (usually a [`Sensor`]) that controllers with [`Swimming`] swim in while overlapping.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct WaterVolume {
+    /// Height of the water's surface above the volume's origin, along the controller's up vector.
+    pub surface_offset: f32,
+}
+
+impl Default for WaterVolume {
+    fn default() -> Self {
+        Self {
+            surface_offset: 0.0,
+        }
+    }
+}
+
+/// Swimming while overlapping a [`WaterVolume`].
+///
+/// While swimming the float force is disabled, gravity is scaled down, buoyancy pulls the
+/// controller towards the waterline and [`ControllerInput::movement`] can move up and down.
+///
+/// This isn't part of [`Controller`], add it to controllers that should be able to swim.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct Swimming {
+    /// How strongly the controller is pulled towards the waterline.
+    ///
+    /// Only the `y` of per-axis strengths is used, this is ignored while moving up or down.
+    pub buoyancy: Spring,
+    /// How far below the water's surface the controller's origin settles.
+    pub depth: f32,
+    /// Multiplier for gravity while swimming.
+    pub gravity_scale: f32,
+    /// Slows the controller down by this fraction of its velocity per second.
+    pub drag: f32,
+    /// How fast the controller swims.
+    pub speed: f32,
+    /// How fast the controller gets to `speed`.
+    pub acceleration: Strength,
+    /// Water volume the controller is currently swimming in.
+    pub water: Option<Entity>,
+}
+
+impl Default for Swimming {
+    fn default() -> Self {
+        Self {
+            buoyancy: Spring {
+                strength: SpringStrength::AngularFrequency(4.0),
+                damping: 1.0,
+            },
+            depth: 0.3,
+            gravity_scale: 0.0,
+            drag: 1.0,
+            speed: 3.0,
+            acceleration: Strength::Scaled(5.0),
+            water: None,
+        }
+    }
+}
+
+impl Swimming {
+    /// Are we currently in the water?
+    pub fn swimming(&self) -> bool {
+        self.water.is_some()
+    }
+}
+
+/// Controllers that can swim, and the forces replaced while in water.
+type SwimQuery = (
+    Entity,
+    &'static GlobalTransform,
+    &'static Collider,
+    &'static mut Swimming,
+    &'static ControllerInput,
+    &'static Gravity,
+    &'static ControllerVelocity,
+    &'static ControllerMass,
+    &'static mut FloatForce,
+    &'static mut GravityForce,
+    &'static mut MovementForce,
+);
+
+/// Find the water controllers are in, and replace their float/gravity/movement forces with swimming.
+pub fn swim(
+    mut query: Query<SwimQuery>,
+    volumes: Query<(&WaterVolume, &GlobalTransform)>,
+    ctx: Res<RapierContext>,
+) {
+    let dt = ctx.integration_parameters.dt;
+    for (
+        entity,
+        tf,
+        collider,
+        mut swimming,
+        input,
+        gravity,
+        velocity,
+        mass,
+        mut float_force,
+        mut gravity_force,
+        mut movement_force,
+    ) in &mut query
+    {
+        let (_, rotation, position) = tf.to_scale_rotation_translation();
+        let predicate = |hit| hit != entity && volumes.contains(hit);
+        let filter = QueryFilter::new().predicate(&predicate);
+
+        swimming.water = None;
+        ctx.intersections_with_shape(position, rotation, collider, filter, |hit| {
+            swimming.water = Some(hit);
+            false
+        });
+
+        let water = swimming.water.and_then(|water| volumes.get(water).ok());
+        let Some((water, water_tf)) = water else { continue };
+
+        let up = gravity.up_vector;
+        let waterline =
+            (water_tf.translation() - position).dot(up) + water.surface_offset - swimming.depth;
+
+        float_force.linear = Vec3::ZERO;
+        gravity_force.linear *= swimming.gravity_scale;
+
+        let input = input.movement.clamp_length_max(1.0);
+        let goal = input * swimming.speed;
+        let strength = swimming.acceleration.get(mass.mass, dt);
+        movement_force.linear = (goal - velocity.linear) * strength;
+        movement_force.linear -= velocity.linear * swimming.drag * mass.mass;
+
+        // Let the controller dive/surface, otherwise bob at the waterline.
+        if input.dot(up).abs() <= f32::EPSILON {
+            let mass = Vec3::splat(mass.mass);
+            let strength = waterline * swimming.buoyancy.strength.get(mass).y;
+            let damping = velocity.vertical_speed(up) * swimming.buoyancy.damp_coefficient(mass).y;
+            movement_force.linear += up * (strength - damping);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::controller::*;
+use bevy::utils::HashSet;
+
+/// How to detect walls next to the controller.
+///
+/// This isn't part of [`Controller`], add it along with a [`WallCast`]
+/// to controllers that need to know about walls (e.g. for [`WallJump`]).
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct WallCaster {
+    /// How far out from the controller's collider to look for walls.
+    pub cast_length: f32,
+    /// How many directions around the controller (perpendicular to the up vector) to cast in.
+    pub cast_directions: usize,
+    /// What shape to cast. See [`Collider`] and [`RapierContext::cast_shape`](bevy_rapier::prelude::RapierContext).
+    ///
+    /// The default is the controller's base collider.
+    #[reflect(ignore)]
+    pub cast_collider: Option<Collider>,
+    /// Set of entities that should be ignored when wall casting.
+    pub exclude_from_wall: HashSet<Entity>,
+    /// How much, in radians, the wall normal has to change while touching
+    /// the same wall for another [`WallContactEvent`] to be sent.
+    pub event_normal_threshold: f32,
+    /// Wall from the last [`WallContactEvent`] sent.
+    #[reflect(ignore)]
+    pub reported_wall: Option<Wall>,
+    /// Every wall found this frame, one per cast direction that hit. The closest is in [`WallCast`].
+    #[reflect(ignore)]
+    pub contacts: Vec<Wall>,
+}
+
+impl Default for WallCaster {
+    fn default() -> Self {
+        Self {
+            cast_length: 0.1,
+            cast_directions: 8,
+            cast_collider: None,
+            exclude_from_wall: default(),
+            event_normal_threshold: 15.0 * (std::f32::consts::PI / 180.0),
+            reported_wall: None,
+            contacts: Vec::new(),
+        }
+    }
+}
+
+/// Information about the wall entity/where we are touching it.
+#[derive(Copy, Clone)]
+pub struct Wall {
+    /// Entity found in wall cast.
+    pub entity: Entity,
+    /// Specifics of the wall contact.
+    pub cast: CastResult,
+}
+
+/// The closest wall found this frame, if any.
+///
+/// A wall is any surface too steep to be considered ground, see [`GroundCaster::max_ground_angle`].
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct WallCast(pub Option<Wall>);
+
+/// Sent when a controller starts touching a wall, or the normal of the wall it's touching changes.
+#[derive(Event, Debug, Clone)]
+pub struct WallContactEvent {
+    /// Controller touching the wall.
+    pub entity: Entity,
+    /// Normal of the wall.
+    pub normal: Vec3,
+    /// Wall entity.
+    pub wall: Entity,
+}
+
+/// Find the closest wall next to the controller.
+pub fn find_wall(
+    mut casters: Query<(
+        Entity,
+        &GlobalTransform,
+        &Gravity,
+        &GroundCaster,
+        &mut WallCaster,
+        &mut WallCast,
+    )>,
+    colliders: Query<&Collider>,
+    ctx: Res<RapierContext>,
+    #[cfg(feature = "debug_lines")] mut gizmos: Gizmos,
+    #[cfg(feature = "debug_lines")] debug: Res<crate::WanderlustDebugConfig>,
+    mut events: EventWriter<WallContactEvent>,
+) {
+    #[cfg(feature = "debug_lines")]
+    let mut gizmos = debug.casts.then_some(&mut gizmos);
+    #[cfg(not(feature = "debug_lines"))]
+    let mut gizmos: Option<&mut Gizmos> = None;
+
+    for (entity, tf, gravity, ground_caster, mut caster, mut wall) in &mut casters {
+        wall.0 = None;
+        // Compare against the last reported wall rather than last frame's,
+        // so slowly curving walls still send events.
+        let previous = caster.reported_wall;
+
+        // Reuse last frame's contacts to avoid allocating every frame.
+        let mut contacts = std::mem::take(&mut caster.contacts);
+        contacts.clear();
+
+        let Ok(caster_collider) = colliders.get(entity) else { continue };
+        let shape = caster.cast_collider.as_ref().unwrap_or(caster_collider);
+
+        let predicate =
+            |collider| collider != entity && !caster.exclude_from_wall.contains(&collider);
+        let filter = QueryFilter::new().exclude_sensors().predicate(&predicate);
+
+        let (x, z) = gravity.up_vector.any_orthonormal_pair();
+        let (_, rotation, position) = tf.to_scale_rotation_translation();
+
+        for index in 0..caster.cast_directions {
+            let angle = index as f32 / caster.cast_directions as f32 * std::f32::consts::TAU;
+            let direction = x * angle.cos() + z * angle.sin();
+
+            let params = GroundCastParams {
+                position,
+                rotation,
+                direction,
+                shape,
+                max_toi: caster.cast_length,
+                filter,
+                kind: GroundCastKind::Shape,
+            };
+
+            let Some((hit_entity, cast)) = params
+                .cast_shape(&ctx, gizmos.as_deref_mut())
+                .or_else(|| params.cast_ray(&ctx)) else { continue };
+
+            // Anything we could stand on isn't a wall.
+            if cast.normal.length_squared() == 0.0
+                || cast.viable(gravity.up_vector, ground_caster.max_ground_angle)
+            {
+                continue;
+            }
+
+            let hit = Wall {
+                entity: ctx.collider_parent(hit_entity).unwrap_or(hit_entity),
+                cast,
+            };
+            contacts.push(hit);
+
+            let closer = match wall.0 {
+                Some(current) => cast.toi < current.cast.toi,
+                None => true,
+            };
+
+            if closer {
+                wall.0 = Some(hit);
+            }
+        }
+
+        caster.contacts = contacts;
+
+        if let Some(current) = wall.0 {
+            let changed = match previous {
+                Some(previous) => {
+                    previous.entity != current.entity
+                        || previous.cast.normal.angle_between(current.cast.normal)
+                            > caster.event_normal_threshold
+                }
+                None => true,
+            };
+
+            if changed {
+                events.send(WallContactEvent {
+                    entity,
+                    normal: current.cast.normal,
+                    wall: current.entity,
+                });
+                caster.reported_wall = Some(current);
+            }
+        } else {
+            caster.reported_wall = None;
+        }
+    }
+}
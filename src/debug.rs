@@ -0,0 +1,101 @@
+use crate::{controller::*, physics::*, ControllerInput};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Which parts of the controllers to visualize with [`Gizmos`], see [`debug_controllers`].
+///
+/// Only available with the `debug_lines` feature.
+#[derive(Resource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct WanderlustDebugConfig {
+    /// Ray down to the current float distance, with the floating target height marked.
+    pub float: bool,
+    /// Contact point and normal of the viable ground.
+    pub ground: bool,
+    /// Force pushed back onto the ground, red when pushing the ground down and blue otherwise.
+    pub ground_force: bool,
+    /// Target velocity from the movement input.
+    pub movement: bool,
+    /// Change in velocity from the jump force.
+    pub jump: bool,
+    /// Shapes, rays and sampled normals of the ground, step and wall casts.
+    pub casts: bool,
+}
+
+impl Default for WanderlustDebugConfig {
+    fn default() -> Self {
+        Self {
+            float: true,
+            ground: true,
+            ground_force: true,
+            movement: true,
+            jump: true,
+            casts: true,
+        }
+    }
+}
+
+/// Controller state drawn by [`debug_controllers`].
+type DebugControllerQuery = (
+    &'static GlobalTransform,
+    &'static Gravity,
+    &'static Float,
+    &'static ViableGroundCast,
+    &'static ControllerInput,
+    &'static Movement,
+    &'static JumpForce,
+    &'static GroundForce,
+    &'static ControllerMass,
+);
+
+/// Draw debug gizmos for controllers based on the [`WanderlustDebugConfig`].
+pub fn debug_controllers(
+    query: Query<DebugControllerQuery>,
+    config: Res<WanderlustDebugConfig>,
+    ctx: Res<RapierContext>,
+    mut gizmos: Gizmos,
+) {
+    let dt = ctx.integration_parameters.dt;
+    for (tf, gravity, float, viable_ground, input, movement, jump_force, ground_force, mass) in
+        &query
+    {
+        let position = tf.translation();
+        let up = gravity.up_vector;
+
+        if config.float {
+            let distance = float.current_distance.unwrap_or(float.distance);
+            let target = position - up * distance;
+            gizmos.ray(position, -up * distance, Color::YELLOW);
+            gizmos.circle(target, up, 0.2, Color::YELLOW);
+        }
+
+        if let (true, Some(ground)) = (config.ground, viable_ground.current()) {
+            gizmos.sphere(ground.cast.point, Quat::IDENTITY, 0.05, Color::LIME_GREEN);
+            gizmos.ray(
+                ground.cast.point,
+                ground.cast.normal * 0.5,
+                Color::LIME_GREEN,
+            );
+        }
+
+        if let (true, Some(ground)) = (config.ground_force, viable_ground.current()) {
+            let color = if ground_force.linear.dot(up) < 0.0 {
+                Color::RED
+            } else {
+                Color::BLUE
+            };
+            let force = ground_force.linear * dt / mass.mass.max(f32::EPSILON);
+            gizmos.ray(ground.cast.point, force, color);
+        }
+
+        if config.movement {
+            let goal =
+                gravity.project(input.movement).clamp_length_max(1.0) * movement.target_speed();
+            gizmos.ray(position, goal * 0.2, Color::GREEN);
+        }
+
+        if config.jump && jump_force.linear != Vec3::ZERO && mass.mass > 0.0 {
+            gizmos.ray(position, jump_force.linear * dt / mass.mass, Color::FUCHSIA);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use bevy::prelude::*;
+
+/// Is the character grounded?
+#[derive(Component, Default, Reflect, Deref)]
+#[reflect(Component, Default)]
+pub struct Grounded(pub bool);
+
+impl Grounded {
+    /// Is the character grounded?
+    pub fn is_grounded(&self) -> bool {
+        self.0
+    }
+}
@@ -0,0 +1,67 @@
+use bevy::ecs::reflect::ReflectComponent;
+use bevy::prelude::{Component, Quat, ReflectDefault, Vec2, Vec3};
+use bevy::reflect::Reflect;
+
+/// This is the interface for applying input to the character controller.
+/// See each field for more information.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct ControllerInput {
+    /// This field represents movement in 3D space.
+    /// The majority of games will map this to WASD/Analog joystick in 2D space along the ground.
+    /// To ensure movement does not affect the Y axis, set [`ControllerSettings::force_scale`] to `Vec3(1.0, 0.0, 1.0)`.
+    ///
+    /// The length (clamped to `1.0`) scales the target speed, so analog input doesn't need to be normalized.
+    /// See [`Movement::input_deadzone`](crate::Movement::input_deadzone).
+    ///
+    /// While [`Swimming`](crate::Swimming) the vertical part is used to swim up and down.
+    pub movement: Vec3,
+    /// This field represents if the jump control is currently pressed.
+    pub jumping: bool,
+    /// This field represents if the sprint control is currently pressed.
+    pub sprinting: bool,
+    /// This field represents if the crouch control is currently pressed. See [`Crouch`](crate::Crouch).
+    pub crouching: bool,
+    /// This field represents if the dash control is currently pressed. See [`Dash`](crate::Dash).
+    pub dashing: bool,
+}
+
+/// Gates for blocking controller actions, e.g. when a stamina system runs out.
+///
+/// This isn't part of [`Controller`](crate::Controller), add it to controllers whose actions can be blocked.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct ActionGates {
+    /// Can the controller start a jump (including wall jumps).
+    ///
+    /// This is checked before a buffered jump is consumed, so a jump pressed while this
+    /// is `false` still goes off if it opens up within [`Jump::buffer_duration`](crate::Jump::buffer_duration).
+    pub can_jump: bool,
+    /// Can the controller sprint, [`ControllerInput::sprinting`] is ignored while this is `false`.
+    pub can_sprint: bool,
+}
+
+impl Default for ActionGates {
+    fn default() -> Self {
+        Self {
+            can_jump: true,
+            can_sprint: true,
+        }
+    }
+}
+
+/// Turn 2D movement input (e.g. a stick or WASD, `y` is forward and `x` is right) into a
+/// [`ControllerInput::movement`] relative to `rotation` (e.g. the camera's), along the
+/// plane perpendicular to `up_vector` (e.g. [`Gravity::up_vector`](crate::Gravity::up_vector)).
+///
+/// The length of `input` is clamped to `1.0` and kept, so analog input still scales the speed.
+pub fn camera_relative_movement(input: Vec2, rotation: Quat, up_vector: Vec3) -> Vec3 {
+    let project = |vector: Vec3| (vector - up_vector * vector.dot(up_vector)).try_normalize();
+    // Looking straight up/down, the top of the view still points forward.
+    let forward = project(rotation * Vec3::NEG_Z).or_else(|| project(rotation * Vec3::Y));
+    let Some(forward) = forward else { return Vec3::ZERO };
+    let right = forward.cross(up_vector);
+
+    let input = input.clamp_length_max(1.0);
+    forward * input.y + right * input.x
+}
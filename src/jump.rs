@@ -0,0 +1,503 @@
+use bevy::prelude::*;
+
+/// How the controller's jumping should behave.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Jump {
+    /// The amount of force to apply on the first frame when a jump begins.
+    pub initial_force: f32,
+    /// The amount of force to continuously apply every second during a jump.
+    pub force: f32,
+    /// Height, in world units, a full jump should reach.
+    ///
+    /// If set, this replaces `initial_force` and `force`. The jump velocity is solved from the
+    /// current gravity and mass on every jump, so the height stays the same when they change.
+    /// This doesn't account for [`Gravity::apex_gravity_scale`](crate::Gravity::apex_gravity_scale), [`Gravity::rise_gravity_scale`](crate::Gravity::rise_gravity_scale) or drag.
+    pub desired_height: Option<f32>,
+    /// Treat `initial_force`, `force`, `stop_force` and the [`WallJump`](crate::WallJump) forces as accelerations,
+    /// scaling them by the controller's mass.
+    ///
+    /// This keeps the jump height the same when the mass (e.g. `ColliderMassProperties`) changes.
+    /// If false, they are raw forces and heavier controllers jump lower.
+    pub mass_relative: bool,
+    /// How long to wait before we can jump again.
+    ///
+    /// This is independent of jump buffering and coyote time, a buffered jump
+    /// will wait for the cooldown to finish.
+    pub cooldown_duration: f32,
+    /// Timer for tracking `cooldown_duration`, this is the remaining cooldown.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub cooldown_timer: f32,
+    /// How long a jump can last.
+    ///
+    /// Holding the jump control for this long gives the full jump height,
+    /// releasing it earlier cuts the jump short (see [`stop_force`](Self::stop_force)).
+    pub jump_duration: f32,
+    /// Timer for tracking `jump_duration`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub jump_timer: f32,
+    /// A function taking the current progress of a jump, from 0.0 to 1.0, with 0.0 indicating a jump has just begun and 1.0 indicating the jump has ended,
+    /// which returns a modifier (usually from 0.0 to 1.0, but not necessarily) to multiply [`jump_force`](ControllerSettings::jump_force) by.
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub decay_function: Option<fn(f32) -> f32>,
+
+    /// Number of times we can jump before we have to touch the ground again.
+    ///
+    /// Every jump after the first is an air jump, which replaces the current
+    /// upwards velocity rather than adding to it.
+    pub jumps: u32,
+    /// Remaining before we have to touch the ground again.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub remaining_jumps: u32,
+    /// Was [`ControllerInput::jumping`](crate::ControllerInput::jumping) true last frame.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub pressed_last_frame: bool,
+    /// The amount of force to apply downwards when the jump control is released prior to a jump expiring.
+    /// This allows analog jumping by cutting the jump short when the control is released.
+    ///
+    /// This is only applied while the controller is still moving upwards.
+    pub stop_force: f32,
+
+    /// A timer to track jump buffering. See [`jump_buffer_duration`](ControllerSettings::jump_buffer_duration)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub buffer_timer: f32,
+    /// If the jump input is pressed before landing, how long will the jump be buffered for?
+    /// In other words, if this is 0.5, the character can input jump up to 0.5 seconds
+    /// before landing and the jump will occur when they land.
+    pub buffer_duration: f32,
+    /// Which jumps a buffered press can set off, see [`JumpBufferMode`].
+    pub buffer_mode: JumpBufferMode,
+
+    /// Do we have to be grounded to jump for the first time?
+    ///
+    /// If this is true, leaving the ground without jumping (after coyote time runs out)
+    /// uses up the first jump, leaving only the air jumps.
+    pub first_jump_grounded: bool,
+    /// How long should the character still be able to jump after leaving the ground, in seconds.
+    /// For example, if this is set to 0.5, the player can fall off a ledge and then jump if they do so within 0.5 seconds of leaving the ledge.
+    pub coyote_duration: f32,
+    /// A timer to track coyote time. See [`coyote_duration`](Self::coyote_duration)
+    ///
+    /// Only counts down while airborne, this is the remaining coyote time.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub coyote_timer: f32,
+
+    /// How long to skip ground checks after jumping. Usually this should be set just high enough that the character is out of range of the ground
+    /// just before the timer elapses.
+    pub skip_ground_check_duration: f32,
+
+    /// Should the velocity of the ground we jumped off of (e.g. a moving platform) carry over into the jump?
+    ///
+    /// If false, the ground's velocity is cancelled out when the jump begins.
+    pub inherit_ground_velocity: bool,
+
+    /// How much the ground's normal influences the direction of a grounded jump.
+    ///
+    /// At `0.0` the controller jumps straight along the up vector, at `1.0` it jumps
+    /// straight along the normal of the ground it is standing on.
+    pub normal_influence: f32,
+    /// How much an air jump redirects the horizontal velocity towards [`ControllerInput::movement`](crate::ControllerInput::movement).
+    ///
+    /// At `0.0` the horizontal velocity is kept as is, at `1.0` all of it is turned towards the
+    /// input, keeping the same speed. Ground and coyote jumps are unaffected.
+    pub air_jump_redirect: f32,
+    /// Where the current jump started, cleared once its apex is reached. See [`ApexEvent`](crate::ApexEvent).
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub apex_origin: Option<Vec3>,
+}
+
+/// Which jumps a buffered jump press (see [`Jump::buffer_duration`]) can set off.
+///
+/// Whichever way a press is used, it only ever sets off a single jump. Jumping clears the
+/// buffer and the coyote time, so a press buffered into coyote time becomes a coyote jump
+/// and doesn't jump again on landing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JumpBufferMode {
+    /// Jump as soon as any jump is possible, including air jumps, wall jumps and coyote jumps.
+    ///
+    /// E.g. pressing jump while the cooldown is running air jumps once it ends.
+    #[default]
+    Any,
+    /// Only jump off of the ground or during coyote time, air and wall jumps need a fresh press.
+    ///
+    /// E.g. pressing jump just before landing jumps on landing, rather than spending an air
+    /// jump when the cooldown ends.
+    Ground,
+}
+
+impl Default for Jump {
+    fn default() -> Self {
+        Self {
+            initial_force: 30.0,
+            force: 20.0,
+            desired_height: None,
+            mass_relative: false,
+            cooldown_duration: 0.25,
+            cooldown_timer: 0.0,
+            jump_duration: 0.1,
+            jump_timer: 0.0,
+            decay_function: Some(|x| (1.0 - x).sqrt()),
+            stop_force: 0.3,
+
+            buffer_duration: 0.3,
+            buffer_timer: 0.0,
+            buffer_mode: JumpBufferMode::Any,
+
+            first_jump_grounded: true,
+            coyote_duration: 0.2,
+            coyote_timer: 0.0,
+
+            jumps: 1,
+            remaining_jumps: 1,
+            pressed_last_frame: false,
+
+            skip_ground_check_duration: 0.0,
+
+            inherit_ground_velocity: true,
+            normal_influence: 0.0,
+            air_jump_redirect: 0.0,
+            apex_origin: None,
+        }
+    }
+}
+
+impl Jump {
+    /// Tick down timers by `dt`/delta time.
+    pub fn tick_timers(&mut self, dt: f32) {
+        let tick = |timer: &mut f32| {
+            if *timer > 0.0 {
+                *timer = (*timer - dt).max(0.0);
+            }
+        };
+
+        tick(&mut self.cooldown_timer);
+        tick(&mut self.jump_timer);
+        tick(&mut self.buffer_timer);
+        tick(&mut self.coyote_timer);
+    }
+
+    /// Are we currently jumping?
+    pub fn jumping(&self) -> bool {
+        self.jump_timer > 0.0
+    }
+
+    /// Can we jump right now?
+    pub fn can_jump(&self, grounded: bool) -> bool {
+        let first_jump = self.first_jump();
+        //info!("first_jump: {:?}", first_jump);
+        let grounded = grounded || self.in_coyote_time();
+        //info!("grounded: {:?}", grounded);
+        if first_jump && !grounded && self.first_jump_grounded {
+            return false;
+        }
+
+        !self.on_cooldown() && self.remaining_jumps > 0
+    }
+
+    /// Direction to jump in, blended between the up vector and the ground normal
+    /// by [`normal_influence`](Self::normal_influence).
+    pub fn direction(&self, up_vector: Vec3, ground_normal: Vec3) -> Vec3 {
+        let influence = self.normal_influence.clamp(0.0, 1.0);
+        let direction = up_vector.lerp(ground_normal, influence).normalize_or_zero();
+
+        // Never jump into the ground.
+        if direction.dot(up_vector) <= 0.0 {
+            up_vector
+        } else {
+            direction
+        }
+    }
+
+    /// Scale for the jump forces, the `mass` if [`mass_relative`](Self::mass_relative).
+    pub fn force_scale(&self, mass: f32) -> f32 {
+        if self.mass_relative {
+            mass
+        } else {
+            1.0
+        }
+    }
+
+    /// Velocity needed to reach [`desired_height`](Self::desired_height) with gravity's `acceleration`.
+    pub fn desired_velocity(&self, acceleration: f32) -> Option<f32> {
+        self.desired_height
+            .map(|height| (2.0 * acceleration.abs() * height.max(0.0)).sqrt())
+    }
+
+    /// Horizontal velocity after an air jump, turning `horizontal_velocity` towards `direction`
+    /// by [`air_jump_redirect`](Self::air_jump_redirect).
+    pub fn redirect_velocity(&self, horizontal_velocity: Vec3, direction: Vec3) -> Vec3 {
+        let Some(direction) = direction.try_normalize() else { return horizontal_velocity };
+        let redirected = direction * horizontal_velocity.length();
+        horizontal_velocity.lerp(redirected, self.air_jump_redirect.clamp(0.0, 1.0))
+    }
+
+    /// Are we waiting for the jump cooldown to finish?
+    pub fn on_cooldown(&self) -> bool {
+        self.cooldown_timer > 0.0
+    }
+
+    /// Have we not jumped since we last touched the ground?
+    pub fn first_jump(&self) -> bool {
+        self.remaining_jumps == self.jumps
+    }
+
+    /// Number of jumps left that can be done in the air.
+    pub fn air_jumps_remaining(&self) -> u32 {
+        if self.first_jump() && self.first_jump_grounded {
+            self.remaining_jumps.saturating_sub(1)
+        } else {
+            self.remaining_jumps
+        }
+    }
+
+    /// Are we in the grace period after leaving the ground?
+    pub fn in_coyote_time(&self) -> bool {
+        self.coyote_timer > 0.0
+    }
+
+    /// Is there a jump press waiting to be used?
+    pub fn buffered(&self) -> bool {
+        self.buffer_timer > 0.0
+    }
+
+    /// Is there a jump press waiting to be used that [`buffer_mode`](Self::buffer_mode)
+    /// lets set off a jump right now?
+    pub fn buffered_jump(&self, grounded: bool) -> bool {
+        self.buffered()
+            && match self.buffer_mode {
+                JumpBufferMode::Any => true,
+                JumpBufferMode::Ground => grounded || (self.first_jump() && self.in_coyote_time()),
+            }
+    }
+
+    /// Reset the jumping state.
+    pub fn reset_jump(&mut self) {
+        self.remaining_jumps = self.jumps;
+        self.jump_timer = 0.0;
+    }
+
+    /// Update the jump state with the jump control held (`pressed`) or not, after ticking the
+    /// timers with [`tick_timers`](Self::tick_timers).
+    ///
+    /// This refills jumps and coyote time on the ground, forfeits the grounded jump after
+    /// walking off of a ledge and buffers fresh presses. Returns whether a jump is wanted this
+    /// frame, from a fresh press or a buffered one. Check [`can_jump`](Self::can_jump) and then
+    /// call [`start`](Self::start) to actually jump.
+    pub fn update(&mut self, pressed: bool, grounded: bool) -> bool {
+        if grounded {
+            self.coyote_timer = self.coyote_duration;
+            if !self.on_cooldown() {
+                self.reset_jump();
+            }
+        } else if !self.in_coyote_time() && self.first_jump() && self.first_jump_grounded {
+            // We walked off a ledge without jumping, so the grounded jump is forfeit
+            // but we can still use our air jumps.
+            self.remaining_jumps = self.remaining_jumps.saturating_sub(1);
+        }
+
+        let inputted = pressed && !self.pressed_last_frame;
+        let wanted = inputted || self.buffered_jump(grounded);
+        self.pressed_last_frame = pressed;
+
+        // Remember the press in case we can't jump right now,
+        // e.g. we are about to land or are still cooling down.
+        if inputted {
+            self.buffer_timer = self.buffer_duration;
+        }
+
+        wanted
+    }
+
+    /// Start a jump, using up a jump and the buffered press and starting the cooldown.
+    pub fn start(&mut self) {
+        self.remaining_jumps = self.remaining_jumps.saturating_sub(1);
+        self.cooldown_timer = self.cooldown_duration;
+        // Coyote time is only for the jump off of the ground.
+        self.coyote_timer = 0.0;
+        // Consume the buffered press so it doesn't trigger another jump on landing.
+        self.buffer_timer = 0.0;
+        self.jump_timer = self.jump_duration;
+    }
+
+    /// 0..1 progress of the current jump.
+    pub fn jump_progress(&self) -> f32 {
+        (self.jump_duration - self.jump_timer) / self.jump_duration
+    }
+
+    /// Jump force decay multiplier.
+    pub fn decay_multiplier(&self) -> f32 {
+        if let Some(decay_function) = self.decay_function {
+            (decay_function)(self.jump_progress())
+        } else {
+            1.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_tick_down_to_zero() {
+        let mut jump = Jump {
+            cooldown_timer: 0.1,
+            buffer_timer: 0.3,
+            coyote_timer: 0.2,
+            ..default()
+        };
+
+        jump.tick_timers(0.15);
+        assert!(!jump.on_cooldown());
+        assert!(jump.buffered());
+        assert!(jump.in_coyote_time());
+
+        jump.tick_timers(0.15);
+        assert_eq!(jump.buffer_timer, 0.0);
+        assert!(!jump.buffered());
+        assert!(!jump.in_coyote_time());
+    }
+
+    #[test]
+    fn first_jump_needs_ground_or_coyote_time() {
+        let mut jump = Jump::default();
+        assert!(jump.can_jump(true));
+        assert!(!jump.can_jump(false));
+
+        // Just walked off a ledge.
+        jump.coyote_timer = jump.coyote_duration;
+        assert!(jump.can_jump(false));
+
+        jump.first_jump_grounded = false;
+        jump.coyote_timer = 0.0;
+        assert!(jump.can_jump(false));
+    }
+
+    #[test]
+    fn air_jumps_and_cooldown() {
+        let mut jump = Jump {
+            jumps: 2,
+            remaining_jumps: 2,
+            ..default()
+        };
+        assert_eq!(jump.air_jumps_remaining(), 1);
+
+        // Jumped off the ground, one air jump left once the cooldown is done.
+        jump.remaining_jumps = 1;
+        jump.cooldown_timer = jump.cooldown_duration;
+        assert_eq!(jump.air_jumps_remaining(), 1);
+        assert!(!jump.can_jump(false));
+        jump.tick_timers(jump.cooldown_duration);
+        assert!(jump.can_jump(false));
+
+        jump.remaining_jumps = 0;
+        assert!(!jump.can_jump(false));
+        jump.reset_jump();
+        assert!(jump.can_jump(true));
+    }
+
+    #[test]
+    fn any_buffer_mode_allows_air_jumps() {
+        let jump = Jump {
+            jumps: 2,
+            remaining_jumps: 1,
+            buffer_timer: 0.1,
+            buffer_mode: JumpBufferMode::Any,
+            ..default()
+        };
+
+        assert!(jump.buffered_jump(false));
+        assert!(jump.buffered_jump(true));
+        assert!(!Jump::default().buffered_jump(true));
+    }
+
+    #[test]
+    fn ground_buffer_mode_waits_for_ground() {
+        let mut jump = Jump {
+            jumps: 2,
+            remaining_jumps: 1,
+            buffer_timer: 0.1,
+            buffer_mode: JumpBufferMode::Ground,
+            ..default()
+        };
+
+        // Pressed in the air with an air jump left, only jump once we land.
+        assert!(!jump.buffered_jump(false));
+        assert!(jump.buffered_jump(true));
+
+        // Coyote time counts as the ground, but only for the first jump.
+        jump.coyote_timer = 0.1;
+        assert!(!jump.buffered_jump(false));
+        jump.remaining_jumps = jump.jumps;
+        assert!(jump.buffered_jump(false));
+    }
+
+    #[test]
+    fn held_press_jumps_once() {
+        let mut jump = Jump::default();
+        assert!(jump.update(true, true));
+        assert!(jump.can_jump(true));
+        jump.start();
+        assert!(!jump.buffered());
+        assert_eq!(jump.remaining_jumps, 0);
+
+        // Holding the jump control doesn't jump again once back on the ground.
+        jump.tick_timers(jump.cooldown_duration);
+        assert!(!jump.update(true, true));
+        assert_eq!(jump.remaining_jumps, 1);
+
+        // Releasing and pressing again does.
+        assert!(!jump.update(false, true));
+        assert!(jump.update(true, true));
+    }
+
+    #[test]
+    fn walking_off_a_ledge_forfeits_the_grounded_jump() {
+        let mut jump = Jump {
+            jumps: 2,
+            ..default()
+        };
+        jump.update(false, true);
+        assert_eq!(jump.remaining_jumps, 2);
+
+        // Coyote time keeps the grounded jump for a while.
+        jump.tick_timers(0.1);
+        jump.update(false, false);
+        assert_eq!(jump.remaining_jumps, 2);
+
+        jump.tick_timers(jump.coyote_duration);
+        jump.update(false, false);
+        assert_eq!(jump.remaining_jumps, 1);
+        assert!(jump.can_jump(false));
+    }
+
+    #[test]
+    fn desired_height_reaches_apex() {
+        let jump = Jump {
+            desired_height: Some(2.0),
+            ..default()
+        };
+
+        for acceleration in [-9.81, -20.0, -30.0, -60.0] {
+            let mut velocity = jump.desired_velocity(acceleration).unwrap();
+            let mut height = 0.0f32;
+            let dt = 1.0 / 1000.0;
+            while velocity > 0.0 {
+                let next = velocity + acceleration * dt;
+                height += (velocity + next) / 2.0 * dt;
+                velocity = next;
+            }
+
+            // The last step goes slightly past the apex and back down.
+            assert!((height - 2.0).abs() < 1e-2, "{acceleration}: {height}");
+        }
+    }
+}
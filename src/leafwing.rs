@@ -0,0 +1,70 @@
+use crate::ControllerInput;
+use bevy::prelude::*;
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+
+/// Actions for driving a [`ControllerInput`] through [`leafwing_input_manager`].
+///
+/// Add an [`InputManagerBundle<WanderlustAction>`] to the controller, see [`WanderlustAction::default_input_map`].
+#[derive(Actionlike, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum WanderlustAction {
+    /// Dual axis movement, `y` is forward.
+    Move,
+    /// See [`ControllerInput::jumping`].
+    Jump,
+    /// See [`ControllerInput::sprinting`].
+    Sprint,
+    /// See [`ControllerInput::crouching`].
+    Crouch,
+    /// See [`ControllerInput::dashing`].
+    Dash,
+}
+
+impl WanderlustAction {
+    /// Keyboard (WASD, space, shift, control, alt) and gamepad bindings.
+    pub fn default_input_map() -> InputMap<Self> {
+        let mut input_map = InputMap::default();
+        input_map
+            .insert(VirtualDPad::wasd(), Self::Move)
+            .insert(DualAxis::left_stick(), Self::Move)
+            .insert(KeyCode::Space, Self::Jump)
+            .insert(GamepadButtonType::South, Self::Jump)
+            .insert(KeyCode::ShiftLeft, Self::Sprint)
+            .insert(GamepadButtonType::LeftThumb, Self::Sprint)
+            .insert(KeyCode::ControlLeft, Self::Crouch)
+            .insert(GamepadButtonType::East, Self::Crouch)
+            .insert(KeyCode::AltLeft, Self::Dash)
+            .insert(GamepadButtonType::West, Self::Dash);
+        input_map
+    }
+}
+
+/// Copy the [`ActionState<WanderlustAction>`] of controllers into their [`ControllerInput`].
+///
+/// Movement is along the world `X`/`-Z` plane, rotate [`ControllerInput::movement`] in a later
+/// system for camera-relative movement.
+pub fn leafwing_input(mut query: Query<(&ActionState<WanderlustAction>, &mut ControllerInput)>) {
+    for (actions, mut input) in &mut query {
+        let movement = actions
+            .clamped_axis_pair(WanderlustAction::Move)
+            .map_or(Vec2::ZERO, |axis| axis.xy());
+
+        input.movement = Vec3::new(movement.x, 0.0, -movement.y);
+        input.jumping = actions.pressed(WanderlustAction::Jump);
+        input.sprinting = actions.pressed(WanderlustAction::Sprint);
+        input.crouching = actions.pressed(WanderlustAction::Crouch);
+        input.dashing = actions.pressed(WanderlustAction::Dash);
+    }
+}
+
+/// Drive [`ControllerInput`] from [`WanderlustAction`]s, see [`leafwing_input`].
+pub struct WanderlustInputPlugin;
+
+impl Plugin for WanderlustInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(InputManagerPlugin::<WanderlustAction>::default())
+            .add_systems(
+                PreUpdate,
+                leafwing_input.after(InputManagerSystem::ManualControl),
+            );
+    }
+}
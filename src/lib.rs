@@ -5,16 +5,40 @@
 )]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "rapier")]
 mod bundles;
+#[cfg(feature = "rapier")]
 mod controller;
+#[cfg(feature = "debug_lines")]
+mod debug;
+mod grounded;
+mod input;
+mod jump;
+#[cfg(feature = "leafwing")]
+mod leafwing;
 mod physics;
+#[cfg(feature = "rapier")]
 mod plugins;
 mod spring;
 
 #[cfg(feature = "rapier")]
 mod rapier;
+#[cfg(feature = "rapier2d")]
+mod rapier2d;
 
+pub use {grounded::*, input::*, jump::*, physics::*, spring::*};
+
+#[cfg(feature = "rapier")]
 pub use {
-    bundles::ControllerBundle, controller::*, physics::*, plugins::WanderlustPlugin, rapier::*,
-    spring::*,
+    bundles::{ControllerBuilder, ControllerBundle},
+    controller::*,
+    plugins::{WanderlustPhysicsTweaks, WanderlustPlugin, WanderlustSet},
+    rapier::*,
 };
+
+#[cfg(feature = "debug_lines")]
+pub use debug::*;
+#[cfg(feature = "leafwing")]
+pub use leafwing::*;
+#[cfg(feature = "rapier2d")]
+pub use rapier2d::*;
@@ -22,6 +22,25 @@ pub struct ControllerVelocity {
     pub angular: Vec3,
 }
 
+impl ControllerVelocity {
+    /// Linear velocity perpendicular to `up_vector`, i.e. along the ground plane.
+    ///
+    /// This is the same projection as [`Gravity::project`](crate::Gravity::project).
+    pub fn horizontal_velocity(&self, up_vector: Vec3) -> Vec3 {
+        self.linear - up_vector * self.linear.dot(up_vector)
+    }
+
+    /// Speed perpendicular to `up_vector`.
+    pub fn horizontal_speed(&self, up_vector: Vec3) -> f32 {
+        self.horizontal_velocity(up_vector).length()
+    }
+
+    /// Signed speed along `up_vector`, positive is moving up.
+    pub fn vertical_speed(&self, up_vector: Vec3) -> f32 {
+        self.linear.dot(up_vector)
+    }
+}
+
 /// Force applied to the controller.
 #[derive(Copy, Clone, Component, Default, Reflect)]
 #[reflect(Component, Default)]
@@ -52,3 +71,70 @@ impl Default for ControllerPhysicsBundle {
         }
     }
 }
+
+/// Defines a system setting rapier's `integration_parameters.dt` to the length of the next
+/// physics step, for whichever of `bevy_rapier2d`/`bevy_rapier3d` is imported where it is used.
+#[cfg(any(feature = "rapier", feature = "rapier2d"))]
+macro_rules! sync_physics_dt_system {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        pub fn $name(
+            mut ctx: ResMut<RapierContext>,
+            config: Res<RapierConfiguration>,
+            time: Res<Time>,
+        ) {
+            let dt = match config.timestep_mode {
+                TimestepMode::Fixed { dt, .. } => dt,
+                TimestepMode::Variable {
+                    max_dt, time_scale, ..
+                } => (time.delta_seconds() * time_scale).min(max_dt),
+                TimestepMode::Interpolated { dt, time_scale, .. } => dt * time_scale,
+            };
+
+            // Keep the last dt while paused so we don't divide by zero.
+            if dt > 0.0 && ctx.integration_parameters.dt != dt {
+                ctx.integration_parameters.dt = dt;
+            }
+        }
+    };
+}
+#[cfg(any(feature = "rapier", feature = "rapier2d"))]
+pub(crate) use sync_physics_dt_system;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn velocity_split_along_y_up() {
+        let velocity = ControllerVelocity {
+            linear: Vec3::new(3.0, -2.0, 4.0),
+            angular: Vec3::ZERO,
+        };
+
+        let horizontal = velocity.horizontal_velocity(Vec3::Y);
+        assert_eq!(horizontal, Vec3::new(3.0, 0.0, 4.0));
+        assert_eq!(velocity.horizontal_speed(Vec3::Y), 5.0);
+        assert_eq!(velocity.vertical_speed(Vec3::Y), -2.0);
+    }
+
+    #[test]
+    fn velocity_split_along_tilted_up() {
+        // Standing on a wall, e.g. with `SurfaceGravity`.
+        let up = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let velocity = ControllerVelocity {
+            linear: Vec3::new(2.0, 0.0, 1.0),
+            angular: Vec3::ZERO,
+        };
+
+        let horizontal = velocity.horizontal_velocity(up);
+        assert!((horizontal - Vec3::new(1.0, -1.0, 1.0)).length() < 1e-5);
+        assert!(horizontal.dot(up).abs() < 1e-5);
+        assert!((velocity.horizontal_speed(up) - 3.0f32.sqrt()).abs() < 1e-5);
+        assert!((velocity.vertical_speed(up) - 2.0f32.sqrt()).abs() < 1e-5);
+
+        // Both parts add back up to the full velocity.
+        let vertical = up * velocity.vertical_speed(up);
+        assert!((horizontal + vertical - velocity.linear).length() < 1e-5);
+    }
+}
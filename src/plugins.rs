@@ -1,77 +1,260 @@
-use crate::controller::*;
-use bevy::prelude::*;
-use bevy_rapier3d::prelude::*;
+use crate::{controller::*, ControllerInput};
+use bevy::{
+    ecs::schedule::{BoxedScheduleLabel, ScheduleLabel},
+    prelude::*,
+};
+use bevy_rapier3d::{prelude::*, rapier::dynamics::IntegrationParameters};
 
 /// The [character controller](CharacterController) plugin. Necessary to have the character controller
 /// work.
 pub struct WanderlustPlugin {
     tweaks: bool,
+    schedule: BoxedScheduleLabel,
+    physics_in_schedule: bool,
 }
 
 impl WanderlustPlugin {
-    /// Apply tweaks to rapier to try to avoid some jitters/issues.
+    /// Apply tweaks to rapier to try to avoid some jitters/issues, see [`WanderlustPhysicsTweaks`].
     pub fn do_tweaks(tweaks: bool) -> Self {
-        Self { tweaks }
+        Self {
+            tweaks,
+            ..default()
+        }
+    }
+
+    /// Run the controller systems in `schedule` instead of [`Update`], e.g. [`FixedUpdate`] to step
+    /// the controller alongside a fixed physics timestep.
+    ///
+    /// The forces have to be applied right before rapier steps, so rapier's systems are run in
+    /// `schedule` as well, right after [`WanderlustSet::Apply`]. Add rapier with
+    /// `RapierPhysicsPlugin::<NoUserData>::default().with_default_system_setup(false)` so it
+    /// doesn't also step in [`PostUpdate`], and use `TimestepMode::Fixed` with the same period as
+    /// [`FixedTime`] since the forces are calculated using rapier's timestep.
+    ///
+    /// [`ControllerInput`] can still be set in [`Update`], but presses shorter than a fixed step may be missed.
+    pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.schedule = Box::new(schedule);
+        self.physics_in_schedule = true;
+        self
     }
 }
 
 impl Default for WanderlustPlugin {
     fn default() -> Self {
-        Self { tweaks: true }
+        Self {
+            tweaks: true,
+            schedule: Box::new(Update),
+            physics_in_schedule: false,
+        }
     }
 }
 
+/// Stages of the controller, run in this order before rapier steps.
+///
+/// Use these to order your own systems around the controller, e.g. setting [`ControllerInput`]
+/// before [`WanderlustSet::Compute`] or reading the calculated forces after it.
+/// Masses, velocities and ground casts go through the [`PhysicsBackend`], which is rapier's
+/// [`RapierBackend`](crate::RapierBackend) here. Wall, ceiling, step and ledge casts still use the
+/// [`RapierContext`] directly.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WanderlustSet {
+    /// Read the mass/velocity of controllers from the physics engine.
+    Sync,
+    /// Calculate the controller forces.
+    Compute,
+    /// Apply [`ControllerForce`](crate::ControllerForce) and [`GroundForce`] to the physics engine.
+    Apply,
+}
+
 impl Plugin for WanderlustPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<ControllerInput>()
-            .register_type::<Option<Vec3>>();
+            .register_type::<Option<Vec3>>()
+            .register_type::<GroundContact>()
+            .register_type::<ControllerState>()
+            .add_event::<ApexEvent>()
+            .add_event::<CeilingHitEvent>()
+            .add_event::<DashEvent>()
+            .add_event::<JumpEvent>()
+            .add_event::<LandedEvent>()
+            .add_event::<LedgeDetected>()
+            .add_event::<LeftGroundEvent>()
+            .add_event::<MovementStateEvent>()
+            .add_event::<SlideEvent>()
+            .add_event::<StuckEvent>()
+            .add_event::<WallContactEvent>();
 
         if self.tweaks {
-            app.add_systems(Startup, setup_physics_context);
+            app.register_type::<WanderlustPhysicsTweaks>()
+                .init_resource::<WanderlustPhysicsTweaks>()
+                .add_systems(Startup, setup_physics_context);
         }
 
+        app.configure_sets(
+            self.schedule.dyn_clone(),
+            (
+                WanderlustSet::Sync,
+                WanderlustSet::Compute,
+                WanderlustSet::Apply,
+            )
+                .chain()
+                .before(PhysicsSet::SyncBackend),
+        );
+
+        if self.physics_in_schedule {
+            // Rapier's sets only order against ours if they're in the same schedule.
+            type Rapier = RapierPhysicsPlugin<NoUserData>;
+            app.configure_sets(
+                self.schedule.dyn_clone(),
+                (
+                    PhysicsSet::SyncBackend,
+                    PhysicsSet::SyncBackendFlush,
+                    PhysicsSet::StepSimulation,
+                    PhysicsSet::Writeback,
+                )
+                    .chain()
+                    .after(WanderlustSet::Apply),
+            );
+
+            app.add_systems(
+                self.schedule.dyn_clone(),
+                (
+                    Rapier::get_systems(PhysicsSet::SyncBackend).in_set(PhysicsSet::SyncBackend),
+                    Rapier::get_systems(PhysicsSet::SyncBackendFlush)
+                        .in_set(PhysicsSet::SyncBackendFlush),
+                    Rapier::get_systems(PhysicsSet::StepSimulation)
+                        .in_set(PhysicsSet::StepSimulation),
+                    Rapier::get_systems(PhysicsSet::Writeback).in_set(PhysicsSet::Writeback),
+                ),
+            );
+        }
+
+        app.add_systems(
+            self.schedule.dyn_clone(),
+            (
+                crate::sync_physics_dt,
+                sync_mass::<crate::RapierBackend>,
+                sync_velocity::<crate::RapierBackend>,
+            )
+                .chain()
+                .in_set(WanderlustSet::Sync),
+        );
+
         app.add_systems(
-            Update,
+            self.schedule.dyn_clone(),
             (
-                crate::get_mass_from_rapier,
-                crate::get_velocity_from_rapier,
-                find_ground,
+                gravity_volumes,
+                surface_gravity,
+                update_gravity,
+                camera_relative_input,
+                crouch,
+                find_ground::<crate::RapierBackend>,
+                find_wall,
                 determine_groundedness,
-                gravity_force,
-                movement_force,
-                float_force,
-                upright_force,
-                jump_force,
+                step_offset,
+                slide,
+                (
+                    gravity_force,
+                    movement_force,
+                    movement_state,
+                    float_force,
+                    upright_force,
+                    swim,
+                    jump_force,
+                    ceiling_bonk,
+                    jump_apex,
+                    dash_force,
+                    knockback_force,
+                    ledge_grab,
+                )
+                    .chain(),
+                controller_state,
+                stuck_detection,
+                camera_motion,
                 accumulate_forces,
+            )
+                .chain()
+                .in_set(WanderlustSet::Compute),
+        );
+
+        app.add_systems(
+            self.schedule.dyn_clone(),
+            (
                 crate::apply_forces,
                 crate::apply_ground_forces,
+                crate::no_clip_collisions,
             )
                 .chain()
-                .before(PhysicsSet::SyncBackend),
+                .in_set(WanderlustSet::Apply),
         );
 
-        #[cfg(feature = "debug-lines")]
-        app.add_systems(Update, |casts: Query<&GroundCast>, mut gizmos: Gizmos| {
-            for cast in &casts {
-                if let Some((entity, toi, velocity)) = cast.cast {
-                    gizmos.sphere(toi.witness1, Quat::IDENTITY, 0.3, Color::LIME_GREEN);
-                }
-            }
-        });
+        #[cfg(feature = "debug_lines")]
+        app.register_type::<crate::WanderlustDebugConfig>()
+            .init_resource::<crate::WanderlustDebugConfig>()
+            .add_systems(
+                self.schedule.dyn_clone(),
+                crate::debug_controllers.after(WanderlustSet::Compute),
+            );
+    }
+}
+
+/// Tweaks to rapier's integration parameters applied by [`setup_physics_context`] when
+/// [`WanderlustPlugin::do_tweaks`] is enabled.
+///
+/// Insert this before adding the [`WanderlustPlugin`] to change them, `None` leaves rapier's value alone.
+/// Values already changed from rapier's defaults are never overwritten.
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource, Default)]
+pub struct WanderlustPhysicsTweaks {
+    /// Overrides rapier's `erp` (error reduction parameter).
+    ///
+    /// `0.99` prevents noticeable jitter when running facefirst into a wall.
+    pub erp: Option<f32>,
+    /// Overrides rapier's `max_velocity_iterations`.
+    ///
+    /// `16` prevents (most) noticeable jitter when running facefirst into an inverted corner.
+    pub max_velocity_iterations: Option<usize>,
+}
+
+impl Default for WanderlustPhysicsTweaks {
+    fn default() -> Self {
+        Self {
+            erp: Some(0.99),
+            max_velocity_iterations: Some(16),
+        }
     }
 }
 
 /// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.
-/// Alternatively, if one only wants to disable the system, use [`WanderlustPhysicsTweaks`](WanderlustPhysicsTweaks).*
+/// Alternatively, if one only wants to disable the system, use [`WanderlustPlugin::do_tweaks`].*
 ///
-/// This system adds some tweaks to rapier's physics settings that make the character controller behave better.
-pub fn setup_physics_context(/*mut ctx: ResMut<RapierContext>*/) {
-    /*
+/// This system applies the [`WanderlustPhysicsTweaks`] to rapier's physics settings.
+/// Settings that were already changed from rapier's defaults are kept.
+pub fn setup_physics_context(mut ctx: ResMut<RapierContext>, tweaks: Res<WanderlustPhysicsTweaks>) {
+    // Anything that isn't rapier's default has been configured by the user, so leave it alone.
+    let defaults = IntegrationParameters::default();
     let params = &mut ctx.integration_parameters;
-    // This prevents any noticeable jitter when running facefirst into a wall.
-    params.erp = 0.99;
-    // This prevents (most) noticeable jitter when running facefirst into an inverted corner.
-    params.max_velocity_iterations = 16;
-    // TODO: Fix jitter that occurs when running facefirst into a normal corner.
-    */
+    if let Some(erp) = tweaks.erp {
+        if params.erp != defaults.erp {
+            info!("Keeping the configured rapier erp of {}", params.erp);
+        } else if params.erp != erp {
+            info!("Changing rapier erp from {} to {}", params.erp, erp);
+            params.erp = erp;
+        }
+    }
+
+    if let Some(max_velocity_iterations) = tweaks.max_velocity_iterations {
+        if params.max_velocity_iterations != defaults.max_velocity_iterations {
+            info!(
+                "Keeping the configured rapier max_velocity_iterations of {}",
+                params.max_velocity_iterations
+            );
+        } else if params.max_velocity_iterations != max_velocity_iterations {
+            info!(
+                "Changing rapier max_velocity_iterations from {} to {}",
+                params.max_velocity_iterations, max_velocity_iterations
+            );
+            params.max_velocity_iterations = max_velocity_iterations;
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use crate::{controller::*, physics::*};
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_rapier3d::prelude::*;
 
 /// Contains common physics settings for character controllers.
@@ -59,15 +59,62 @@ impl Default for RapierPhysicsBundle {
     }
 }
 
+sync_physics_dt_system! {
+    /// Set rapier's `integration_parameters.dt` to the length of the next physics step.
+    ///
+    /// Rapier steps using the [`TimestepMode`] rather than `integration_parameters.dt`, splitting
+    /// each step into substeps. Controller forces are calculated once per step and applied as an
+    /// impulse before the first substep, so they need the length of the whole step to behave the
+    /// same regardless of the number of substeps.
+    sync_physics_dt
+}
+
 /// Apply forces to the controller to make it float, move, jump, etc.
 pub fn apply_forces(
-    mut forces: Query<(&mut ExternalImpulse, &ControllerForce)>,
+    mut forces: Query<(
+        &mut ExternalImpulse,
+        &ControllerForce,
+        Option<&Freeze>,
+        Option<&mut Velocity>,
+    )>,
     ctx: Res<RapierContext>,
 ) {
     let dt = ctx.integration_parameters.dt;
-    for (mut impulse, force) in &mut forces {
+    for (mut impulse, force, freeze, velocity) in &mut forces {
         impulse.impulse += force.linear * dt;
         impulse.torque_impulse += force.angular * dt;
+
+        if freeze.is_some_and(|freeze| freeze.zero_velocity) {
+            if let Some(mut velocity) = velocity {
+                *velocity = Velocity::zero();
+            }
+        }
+    }
+}
+
+/// Marks a [`Sensor`] that was added by [`NoClip::disable_collisions`], so it can be removed again.
+#[derive(Component)]
+pub struct NoClipSensor;
+
+/// [`NoClip`] controllers whose colliders haven't been turned into sensors yet.
+type NoClipColliders = (Without<Sensor>, Without<NoClipSensor>);
+
+/// Turn [`NoClip`] controllers' colliders into sensors, and back once [`NoClip`] is removed.
+pub fn no_clip_collisions(
+    mut commands: Commands,
+    added: Query<(Entity, &NoClip), NoClipColliders>,
+    sensors: Query<(Entity, Option<&NoClip>), With<NoClipSensor>>,
+) {
+    for (entity, no_clip) in &added {
+        if no_clip.disable_collisions {
+            commands.entity(entity).insert((Sensor, NoClipSensor));
+        }
+    }
+
+    for (entity, no_clip) in &sensors {
+        if !no_clip.is_some_and(|no_clip| no_clip.disable_collisions) {
+            commands.entity(entity).remove::<(Sensor, NoClipSensor)>();
+        }
     }
 }
 
@@ -88,19 +135,103 @@ pub fn apply_ground_forces(
     }
 }
 
-/// Sync rapier masses over to our masses.
-pub fn get_mass_from_rapier(mut query: Query<(&mut ControllerMass, &ReadMassProperties)>) {
-    for (mut mass, rapier_mass) in &mut query {
-        mass.mass = rapier_mass.0.mass;
-        mass.inertia = rapier_mass.0.principal_inertia;
-        mass.com = rapier_mass.0.local_center_of_mass;
-    }
+/// [`PhysicsBackend`] for rapier.
+#[derive(SystemParam)]
+pub struct RapierBackend<'w, 's> {
+    /// Rapier's physics world.
+    pub ctx: Res<'w, RapierContext>,
+    /// Velocities of rigid bodies.
+    pub velocities: Query<'w, 's, &'static Velocity>,
+    /// Mass properties of rigid bodies.
+    pub masses: Query<'w, 's, &'static ReadMassProperties>,
+    /// Transforms of rigid bodies, for their center of mass and correcting ground cast penetrations.
+    pub globals: Query<'w, 's, &'static GlobalTransform>,
+    /// Colliders of controllers, used for casting when [`GroundCaster::cast_collider`] isn't set.
+    pub colliders: Query<'w, 's, &'static Collider>,
 }
 
-/// Sync rapier velocities over to our velocities.
-pub fn get_velocity_from_rapier(mut query: Query<(&mut ControllerVelocity, &Velocity)>) {
-    for (mut vel, rapier_vel) in &mut query {
-        vel.linear = rapier_vel.linvel;
-        vel.angular = rapier_vel.angvel;
+impl PhysicsBackend for RapierBackend<'static, 'static> {
+    fn dt(backend: &RapierBackend) -> f32 {
+        backend.ctx.integration_parameters.dt
+    }
+
+    fn mass(backend: &RapierBackend, entity: Entity) -> Option<ControllerMass> {
+        let mass = backend.masses.get(entity).ok()?;
+        Some(ControllerMass {
+            mass: mass.0.mass,
+            inertia: mass.0.principal_inertia,
+            com: mass.0.local_center_of_mass,
+        })
+    }
+
+    fn velocity(backend: &RapierBackend, entity: Entity) -> Option<ControllerVelocity> {
+        let velocity = backend.velocities.get(entity).ok()?;
+        Some(ControllerVelocity {
+            linear: velocity.linvel,
+            angular: velocity.angvel,
+        })
+    }
+
+    fn point_velocity(backend: &RapierBackend, collider: Entity, point: Vec3) -> PointVelocity {
+        let body = backend.ctx.collider_parent(collider).unwrap_or(collider);
+        let velocity = Self::velocity(backend, body).unwrap_or_default();
+        let local_com = Self::mass(backend, body).unwrap_or_default().com;
+        let com = backend
+            .globals
+            .get(body)
+            .unwrap_or(&GlobalTransform::IDENTITY)
+            .transform_point(local_com);
+
+        PointVelocity {
+            body,
+            velocity,
+            point: velocity.linear + velocity.angular.cross(point - com),
+        }
+    }
+
+    fn cast_ground(
+        backend: &RapierBackend,
+        entity: Entity,
+        caster: &GroundCaster,
+        transform: &GlobalTransform,
+        up_vector: Vec3,
+        gizmos: Option<&mut Gizmos>,
+    ) -> Option<GroundHits> {
+        let caster_collider = backend.colliders.get(entity).ok()?;
+        let shape = caster.cast_collider.as_ref().unwrap_or(caster_collider);
+
+        let predicate =
+            |collider| collider != entity && !caster.exclude_from_ground.contains(&collider);
+        let filter = QueryFilter::new()
+            .exclude_sensors()
+            .groups(caster.ground_groups)
+            .predicate(&predicate);
+
+        let params = GroundCastParams {
+            position: transform.translation(),
+            rotation: transform.to_scale_rotation_translation().1,
+            direction: -up_vector,
+            shape,
+            max_toi: caster.cast_length,
+            filter,
+            kind: caster.cast_kind,
+        };
+        let origins = std::iter::once(Vec3::ZERO)
+            .chain(caster.sample_points.iter().copied())
+            .map(|offset| transform.transform_point(caster.cast_origin + offset))
+            .collect::<Vec<_>>();
+
+        Some(cast_ground(
+            &backend.ctx,
+            &backend.globals,
+            &params,
+            &origins,
+            caster.max_ground_angle,
+            gizmos,
+        ))
+    }
+
+    fn in_contact(backend: &RapierBackend, entity: Entity) -> bool {
+        backend.ctx.contacts_with(entity).next().is_some()
     }
 }
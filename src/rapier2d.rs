@@ -0,0 +1,270 @@
+use crate::{
+    physics::sync_physics_dt_system, ControllerInput, Grounded, Jump, Spring, SpringStrength,
+    Strength,
+};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// A minimal 2D controller for [`bevy_rapier2d`], floating above the ground with the same
+/// spring math and jump handling (coyote time, buffering, air jumps) as the 3D controller.
+///
+/// Up is always `Vec2::Y`, and only the `x` of [`ControllerInput::movement`] is used.
+#[derive(Bundle)]
+pub struct Controller2dBundle {
+    /// See [`ControllerInput`].
+    pub input: ControllerInput,
+    /// See [`Gravity2d`].
+    pub gravity: Gravity2d,
+    /// See [`Float2d`].
+    pub float: Float2d,
+    /// See [`Movement2d`].
+    pub movement: Movement2d,
+    /// See [`Jump`].
+    pub jump: Jump,
+    /// See [`Grounded`].
+    pub grounded: Grounded,
+
+    /// See [`RigidBody`].
+    pub rigidbody: RigidBody,
+    /// See [`Collider`].
+    pub collider: Collider,
+    /// See [`Velocity`].
+    pub velocity: Velocity,
+    /// See [`GravityScale`].
+    pub gravity_scale: GravityScale,
+    /// See [`ExternalImpulse`].
+    pub impulse: ExternalImpulse,
+    /// See [`LockedAxes`].
+    pub locked_axes: LockedAxes,
+    /// See [`Friction`].
+    pub friction: Friction,
+    /// See [`ReadMassProperties`].
+    pub read_mass_properties: ReadMassProperties,
+
+    /// See [`Transform`]
+    pub transform: Transform,
+    /// See [`GlobalTransform`]
+    pub global_transform: GlobalTransform,
+    /// See [`Visibility`]
+    pub visibility: Visibility,
+    /// See [`ComputedVisibility`]
+    pub computed_visibility: ComputedVisibility,
+}
+
+impl Default for Controller2dBundle {
+    fn default() -> Self {
+        Self {
+            input: default(),
+            gravity: default(),
+            float: default(),
+            movement: default(),
+            jump: default(),
+            grounded: default(),
+
+            rigidbody: default(),
+            collider: Collider::capsule_y(0.25, 0.3),
+            velocity: default(),
+            gravity_scale: GravityScale(0.0),
+            impulse: default(),
+            locked_axes: LockedAxes::ROTATION_LOCKED,
+            friction: Friction {
+                coefficient: 0.0,
+                combine_rule: CoefficientCombineRule::Min,
+            },
+            read_mass_properties: default(),
+
+            transform: default(),
+            global_transform: default(),
+            visibility: default(),
+            computed_visibility: default(),
+        }
+    }
+}
+
+/// How strong is the gravity for a 2D controller.
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct Gravity2d {
+    /// Acceleration along `Vec2::Y` due to gravity, see [`Gravity::acceleration`](crate::Gravity::acceleration).
+    pub acceleration: f32,
+}
+
+impl Default for Gravity2d {
+    fn default() -> Self {
+        Self {
+            acceleration: -9.817,
+        }
+    }
+}
+
+/// Keeps a 2D controller floating above the ground, see [`Float`](crate::Float).
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct Float2d {
+    /// How far to attempt to float away from the ground.
+    pub distance: f32,
+    /// How far below the controller's origin to look for the ground.
+    pub cast_length: f32,
+    /// How much further than `distance` the ground can be while still being grounded.
+    pub max_offset: f32,
+    /// How strongly to float away from the ground.
+    pub spring: Spring,
+}
+
+impl Default for Float2d {
+    fn default() -> Self {
+        Self {
+            distance: 0.55,
+            cast_length: 1.05,
+            max_offset: 0.1,
+            spring: Spring {
+                strength: SpringStrength::AngularFrequency(12.0),
+                damping: 0.8,
+            },
+        }
+    }
+}
+
+/// Horizontal movement for a 2D controller, see [`Movement`](crate::Movement).
+#[derive(Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct Movement2d {
+    /// How fast the controller will get to the `max_speed`.
+    pub acceleration: Strength,
+    /// Multiplier for `acceleration` while the controller isn't grounded.
+    pub air_control: f32,
+    /// How fast our controller will move.
+    pub max_speed: f32,
+}
+
+impl Default for Movement2d {
+    fn default() -> Self {
+        Self {
+            acceleration: Strength::Scaled(10.0),
+            air_control: 1.0,
+            max_speed: 5.0,
+        }
+    }
+}
+
+/// Everything [`controller_2d`] reads and writes on a 2D controller.
+type Controller2dQuery = (
+    Entity,
+    &'static GlobalTransform,
+    &'static ControllerInput,
+    &'static Gravity2d,
+    &'static Float2d,
+    &'static Movement2d,
+    &'static mut Jump,
+    &'static mut Grounded,
+    &'static Velocity,
+    &'static ReadMassProperties,
+    &'static mut ExternalImpulse,
+);
+
+sync_physics_dt_system! {
+    /// The 2D counterpart of `sync_physics_dt`, run by [`Wanderlust2dPlugin`] before
+    /// [`controller_2d`].
+    sync_physics_dt_2d
+}
+
+/// Calculate and apply the float, movement, jump and gravity forces of 2D controllers.
+pub fn controller_2d(mut query: Query<Controller2dQuery>, ctx: Res<RapierContext>) {
+    let dt = ctx.integration_parameters.dt;
+    for (
+        entity,
+        tf,
+        input,
+        gravity,
+        float,
+        movement,
+        mut jump,
+        mut grounded,
+        velocity,
+        mass,
+        mut impulse,
+    ) in &mut query
+    {
+        let mass = mass.0.mass;
+        let velocity = velocity.linvel;
+        jump.tick_timers(dt);
+
+        let predicate = |collider| collider != entity;
+        let filter = QueryFilter::new().exclude_sensors().predicate(&predicate);
+        let ground = ctx.cast_ray(
+            tf.translation().truncate(),
+            Vec2::NEG_Y,
+            float.cast_length,
+            true,
+            filter,
+        );
+
+        grounded.0 = !jump.jumping()
+            && ground.is_some_and(|(_, toi)| toi <= float.distance + float.max_offset);
+
+        let mut gravity_force = Vec2::Y * gravity.acceleration * mass;
+
+        let mut float_force = Vec2::ZERO;
+        if let (Some((_, toi)), false) = (ground, jump.jumping()) {
+            let displacement = float.distance - toi;
+            if displacement > 0.0 || grounded.0 {
+                let mass = Vec3::splat(mass);
+                let strength = displacement * float.spring.strength.get(mass).y;
+                let damping = velocity.y * float.spring.damp_coefficient(mass).y;
+                float_force.y = strength - damping;
+            }
+        }
+
+        let goal = input.movement.x.clamp(-1.0, 1.0) * movement.max_speed;
+        let difference = goal - velocity.x;
+        let air_control = if grounded.0 {
+            1.0
+        } else {
+            movement.air_control
+        };
+        let strength = movement.acceleration.get(mass, dt) * air_control;
+        let max_force = difference.abs() * mass / dt;
+        let movement_force = Vec2::X * (difference * strength).clamp(-max_force, max_force);
+
+        let mut jump_force = Vec2::ZERO;
+        let just_jumped = jump.update(input.jumping, grounded.0) && jump.can_jump(grounded.0);
+        if just_jumped {
+            // Negate the current vertical velocity, same as the 3D controller.
+            jump_force.y = -velocity.y * mass / dt + jump.initial_force * jump.force_scale(mass);
+            gravity_force = Vec2::ZERO;
+            float_force = Vec2::ZERO;
+
+            jump.start();
+            grounded.0 = false;
+        } else if jump.jumping() {
+            if !input.jumping {
+                if velocity.y > 0.0 {
+                    jump_force.y = -velocity.y * jump.stop_force * jump.force_scale(mass);
+                }
+            } else {
+                jump_force.y = jump.force * jump.force_scale(mass) * jump.decay_multiplier();
+            }
+        }
+
+        let force = gravity_force + float_force + movement_force + jump_force;
+        impulse.impulse += force * dt;
+    }
+}
+
+/// The 2D counterpart of [`WanderlustPlugin`](crate::WanderlustPlugin), see [`Controller2dBundle`].
+pub struct Wanderlust2dPlugin;
+
+impl Plugin for Wanderlust2dPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ControllerInput>()
+            .register_type::<Gravity2d>()
+            .register_type::<Float2d>()
+            .register_type::<Movement2d>()
+            .add_systems(
+                Update,
+                (sync_physics_dt_2d, controller_2d)
+                    .chain()
+                    .before(PhysicsSet::SyncBackend),
+            );
+    }
+}
@@ -2,6 +2,7 @@ use bevy::{math::*, prelude::*};
 
 /// Generic strength argument to springs and movement arguments.
 #[derive(Debug, Clone, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Strength {
     /// Scaled by the mass and delta time.
     ///
@@ -15,7 +16,16 @@ pub enum Strength {
     Scaled(f32),
     /// Unaffected force, this will be applied regardless of any
     /// other factors.
+    ///
+    /// This is the only mode that isn't mass-relative, heavier controllers will respond slower.
     Raw(f32),
+    /// Scaled by the mass like [`Strength::Scaled`], but varies with progress towards the goal.
+    ///
+    /// Each point is `(progress, strength)`, sorted by progress from 0..1, and the strength is
+    /// linearly interpolated between them. For movement the progress is the fraction of
+    /// the max speed we are currently moving at, so e.g. `[(0.0, 20.0), (1.0, 5.0)]` accelerates
+    /// quickly from a standstill and tapers off near max speed.
+    Curve(Vec<Vec2>),
 }
 
 impl Strength {
@@ -26,30 +36,78 @@ impl Strength {
 
     /// Calculate strength, prevents overshooting.
     pub fn get(&self, mass: f32, dt: f32) -> f32 {
+        self.get_with_progress(0.0, mass, dt)
+    }
+
+    /// Calculate strength given the 0..1 progress towards the goal, prevents overshooting.
+    ///
+    /// Progress only matters for [`Strength::Curve`].
+    pub fn get_with_progress(&self, progress: f32, mass: f32, dt: f32) -> f32 {
         let max = Self::max().uncapped(mass, dt);
-        let uncapped = self.uncapped(mass, dt);
+        let uncapped = self.uncapped_with_progress(progress, mass, dt);
         uncapped.min(max)
     }
 
     /// Calculate strength without capping before we overshoot
     /// and start extrapolating.
     pub fn uncapped(&self, mass: f32, dt: f32) -> f32 {
+        self.uncapped_with_progress(0.0, mass, dt)
+    }
+
+    /// Calculate strength given the 0..1 progress towards the goal without capping.
+    pub fn uncapped_with_progress(&self, progress: f32, mass: f32, dt: f32) -> f32 {
         match *self {
             Self::Instant(raw) => raw * mass / dt,
             Self::Scaled(raw) => raw * mass,
             Self::Raw(raw) => raw,
+            Self::Curve(ref points) => sample_curve(points, progress) * mass,
+        }
+    }
+}
+
+/// Linearly interpolate between `(x, y)` points sorted by `x`.
+fn sample_curve(points: &[Vec2], x: f32) -> f32 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else { return 0.0 };
+
+    if x <= first.x {
+        return first.y;
+    }
+
+    for window in points.windows(2) {
+        let (a, b) = (window[0], window[1]);
+        if x <= b.x {
+            let t = if b.x > a.x {
+                (x - a.x) / (b.x - a.x)
+            } else {
+                1.0
+            };
+            return a.y + (b.y - a.y) * t;
         }
     }
+
+    last.y
 }
 
 /// Methods of calculating strength for a spring.
 #[derive(Debug, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpringStrength {
-    /// Desired angular frequency for this spring.
+    /// Desired angular frequency for this spring, in radians per second.
     /// This takes into account mass.
+    ///
+    /// An undamped spring oscillates `frequency / 2π` times per second,
+    /// so higher values reach the target faster.
     AngularFrequency(f32),
     /// Raw stiffness coefficient for the `F = -kv - cv` function.
+    ///
+    /// Unlike [`SpringStrength::AngularFrequency`] this isn't mass-relative, so e.g. a heavier
+    /// controller floats lower.
     StiffnessCoefficient(f32),
+    /// [`SpringStrength::AngularFrequency`] with a separate frequency for each axis,
+    /// e.g. a stiff vertical axis with softer horizontal axes.
+    AngularFrequencyPerAxis(Vec3),
+    /// [`SpringStrength::StiffnessCoefficient`] with a separate coefficient for each axis.
+    StiffnessCoefficientPerAxis(Vec3),
 }
 
 impl SpringStrength {
@@ -58,6 +116,8 @@ impl SpringStrength {
         match *self {
             Self::AngularFrequency(angular) => mass * angular * angular,
             Self::StiffnessCoefficient(raw) => Vec3::splat(raw),
+            Self::AngularFrequencyPerAxis(angular) => mass * angular * angular,
+            Self::StiffnessCoefficientPerAxis(raw) => raw,
         }
     }
 }
@@ -68,6 +128,11 @@ impl SpringStrength {
 /// - https://www.ryanjuckett.com/damped-springs/
 /// - https://gafferongames.com/post/spring_physics/
 #[derive(Debug, Clone, Copy, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Spring {
     /// How strong the spring will push it into position.
     pub strength: SpringStrength,
@@ -100,4 +165,205 @@ impl Spring {
     pub fn damp_coefficient(&self, inertia: Vec3) -> Vec3 {
         self.damping * self.critical_damping_point(inertia)
     }
+
+    /// Spring force (`F = kx - cv`) on a body with `mass`, solved implicitly over a physics step of `dt`.
+    ///
+    /// `displacement` is how far the body is from its rest point (`x`), `velocity` is its velocity
+    /// relative to that point and `external` is any other force acting on it over the step, e.g. gravity.
+    /// The whole velocity update is solved together like [`Spring::integrate`], so stiff springs don't
+    /// overshoot and explode with large steps, and the body still comes to rest where `kx` balances
+    /// `external` regardless of `dt`.
+    ///
+    /// The controller calculates its forces once per physics step using the length of the whole step
+    /// (see [`sync_physics_dt`](crate::sync_physics_dt)), so springs tuned with one number of substeps
+    /// behave the same with any other.
+    pub fn implicit_force(
+        &self,
+        mass: Vec3,
+        displacement: Vec3,
+        velocity: Vec3,
+        external: Vec3,
+        dt: f32,
+    ) -> Vec3 {
+        let stiffness = self.strength.get(mass);
+        let damping = self.damp_coefficient(mass);
+        let explicit = displacement * stiffness - velocity * damping;
+        if dt <= 0.0 {
+            return explicit;
+        }
+
+        let massive = mass.cmpgt(Vec3::ZERO);
+        let inv_mass = Vec3::select(massive, mass.recip(), Vec3::ZERO);
+        let scale = Vec3::ONE + (damping * dt + stiffness * dt * dt) * inv_mass;
+        let implicit = (explicit - stiffness * dt * velocity + external) / scale - external;
+        Vec3::select(massive, implicit, explicit)
+    }
+
+    /// Move `position` and `velocity` one step of `dt` towards `target`, returning the new `(position, velocity)`.
+    ///
+    /// This is the same damped spring math the controller uses, with a mass of `1.0`, so it can be
+    /// used to smooth any value (e.g. a camera following the controller). It is integrated implicitly
+    /// so stiff springs stay stable with large `dt`.
+    pub fn integrate(&self, position: Vec3, velocity: Vec3, target: Vec3, dt: f32) -> (Vec3, Vec3) {
+        let stiffness = self.strength.get(Vec3::ONE);
+        let damping = self.damp_coefficient(Vec3::ONE);
+
+        let velocity = (velocity - (position - target) * stiffness * dt)
+            / (Vec3::ONE + damping * dt + stiffness * dt * dt);
+        (position + velocity * dt, velocity)
+    }
+
+    /// Angular velocity after one step of `dt` rotating from `current` towards `target`.
+    ///
+    /// The rotational version of [`Spring::integrate`], always rotating the shortest way around.
+    /// Integrate the result into the rotation (or hand it to the physics engine) to apply it.
+    pub fn integrate_rotation(
+        &self,
+        current: Quat,
+        target: Quat,
+        angular_velocity: Vec3,
+        dt: f32,
+    ) -> Vec3 {
+        let stiffness = self.strength.get(Vec3::ONE);
+        let damping = self.damp_coefficient(Vec3::ONE);
+
+        let displacement = rotation_displacement(current, target);
+        (angular_velocity + displacement * stiffness * dt)
+            / (Vec3::ONE + damping * dt + stiffness * dt * dt)
+    }
+}
+
+/// Scaled axis (axis * angle) of the shortest rotation from `current` to `target`.
+pub fn rotation_displacement(current: Quat, target: Quat) -> Vec3 {
+    let mut rotation = target * current.inverse();
+    // `q` and `-q` are the same rotation, pick the one that doesn't go the long way around.
+    if rotation.w < 0.0 {
+        rotation = -rotation;
+    }
+
+    let (axis, angle) = rotation.to_axis_angle();
+    axis * angle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn critical(frequency: f32) -> Spring {
+        Spring {
+            strength: SpringStrength::AngularFrequency(frequency),
+            damping: 1.0,
+        }
+    }
+
+    #[test]
+    fn integrate_settles_on_target() {
+        let spring = critical(10.0);
+        let target = Vec3::new(1.0, -2.0, 3.0);
+        let (mut position, mut velocity) = (Vec3::ZERO, Vec3::ZERO);
+        for _ in 0..300 {
+            (position, velocity) = spring.integrate(position, velocity, target, 1.0 / 60.0);
+            // Critically damped, so we never go past the target.
+            assert!(position.x <= target.x + 1e-4);
+        }
+
+        assert!((position - target).length() < 1e-3);
+        assert!(velocity.length() < 1e-2);
+    }
+
+    #[test]
+    fn integrate_is_stable_with_large_steps() {
+        let spring = Spring {
+            strength: SpringStrength::AngularFrequency(100.0),
+            damping: 0.25,
+        };
+        let target = Vec3::X;
+        let (mut position, mut velocity) = (Vec3::ZERO, Vec3::ZERO);
+        for _ in 0..20 {
+            (position, velocity) = spring.integrate(position, velocity, target, 0.5);
+            assert!((position - target).length() <= 1.0 + 1e-4);
+        }
+
+        assert!((position - target).length() < 1e-2);
+    }
+
+    #[test]
+    fn integrate_rotation_settles_on_target() {
+        let spring = critical(10.0);
+        let target = Quat::from_rotation_y(1.0) * Quat::from_rotation_x(0.5);
+        let (mut rotation, mut angular_velocity) = (Quat::IDENTITY, Vec3::ZERO);
+        for _ in 0..300 {
+            let dt = 1.0 / 60.0;
+            angular_velocity = spring.integrate_rotation(rotation, target, angular_velocity, dt);
+            rotation = (Quat::from_scaled_axis(angular_velocity * dt) * rotation).normalize();
+        }
+
+        assert!(rotation.angle_between(target) < 1e-3);
+    }
+
+    #[test]
+    fn rotation_displacement_is_scaled_axis() {
+        let displacement = rotation_displacement(Quat::IDENTITY, Quat::from_rotation_y(1.0));
+        assert!((displacement - Vec3::Y).length() < 1e-5);
+
+        let current = Quat::from_rotation_x(0.3);
+        let displacement = rotation_displacement(current, Quat::from_rotation_x(-0.2));
+        assert!((displacement - Vec3::X * -0.5).length() < 1e-5);
+    }
+
+    #[test]
+    fn rotation_displacement_takes_shortest_arc() {
+        use std::f32::consts::PI;
+
+        // 270° one way is 90° the other way.
+        let displacement = rotation_displacement(Quat::IDENTITY, Quat::from_rotation_y(1.5 * PI));
+        assert!((displacement - Vec3::Y * -0.5 * PI).length() < 1e-4);
+
+        // Just past 180° from the current rotation, going the short way around.
+        let current = Quat::from_rotation_z(-2.0);
+        let displacement = rotation_displacement(current, Quat::from_rotation_z(2.0));
+        assert!((displacement - Vec3::Z * (4.0 - 2.0 * PI)).length() < 1e-4);
+        assert!(displacement.length() <= PI);
+    }
+
+    #[test]
+    fn integrate_rotation_stays_at_target() {
+        let spring = critical(10.0);
+        let target = Quat::from_rotation_z(2.0);
+        let angular_velocity = spring.integrate_rotation(target, target, Vec3::ZERO, 1.0 / 60.0);
+        assert!(angular_velocity.length() < 1e-5);
+    }
+
+    /// Integrate `integrate_rotation` from identity towards `target` for 5 seconds, returning the
+    /// final rotation and the furthest it got from `target` along the way.
+    fn settle_rotation(target: Quat) -> (Quat, f32) {
+        let spring = critical(10.0);
+        let dt = 1.0 / 60.0;
+        let (mut rotation, mut angular_velocity) = (Quat::IDENTITY, Vec3::ZERO);
+        let mut furthest = 0.0f32;
+        for _ in 0..300 {
+            angular_velocity = spring.integrate_rotation(rotation, target, angular_velocity, dt);
+            rotation = (Quat::from_scaled_axis(angular_velocity * dt) * rotation).normalize();
+            furthest = furthest.max(rotation.angle_between(target));
+        }
+
+        (rotation, furthest)
+    }
+
+    #[test]
+    fn integrate_rotation_converges_from_170_degrees() {
+        let target = Quat::from_rotation_y(170f32.to_radians());
+        let (rotation, furthest) = settle_rotation(target);
+        assert!(rotation.angle_between(target) < 1e-3);
+        assert!(furthest <= 170f32.to_radians() + 1e-3);
+    }
+
+    #[test]
+    fn integrate_rotation_converges_from_just_past_180_degrees() {
+        // 185° one way is 175° the other, which is the way we should turn.
+        let target = Quat::from_rotation_y(185f32.to_radians());
+        let (rotation, furthest) = settle_rotation(target);
+        assert!(rotation.angle_between(target) < 1e-3);
+        assert!(furthest <= 175f32.to_radians() + 1e-3);
+    }
 }